@@ -0,0 +1,115 @@
+use fmindex::FMIndex;
+
+// backtracking state shared by the recursive searches
+struct Search<'a, 's: 'a, T: 'a> {
+    fmx: &'a FMIndex<'s, T>,
+    alphabet: Vec<u8>,
+    pat: &'a [u8],
+    k: usize,
+}
+
+impl<'a, 's, T> Search<'a, 's, T>
+    where T: std::marker::Copy + num::ToPrimitive {
+
+    // extend pat[i..] to the left with every character, with d mismatches
+    fn hamming(&self, i: usize, d: usize, sp: usize, ep: usize,
+               hits: &mut Vec<(usize, usize)>) {
+        if i == 0 {
+            hits.extend((sp..ep).map(|r| (self.fmx.locate(r), d)));
+            return;
+        }
+        for c in &self.alphabet {
+            let dx = if *c == self.pat[i - 1] { d } else { d + 1 };
+            if dx > self.k {
+                continue;
+            }
+            let (nsp, nep) = self.fmx.extend_left(*c, sp, ep);
+            if nsp < nep {
+                self.hamming(i - 1, dx, nsp, nep, hits);
+            }
+        }
+    }
+
+    // row[j] is the edit distance between the last j characters of pat and
+    // the depth characters matched so far, which prefix the rows [sp, ep)
+    fn edit(&self, row: &[usize], depth: usize, sp: usize, ep: usize,
+            hits: &mut Vec<(usize, usize, usize)>) {
+        let m = self.pat.len();
+        if depth > 0 && row[m] <= self.k {
+            hits.extend((sp..ep).map(|r| (self.fmx.locate(r), depth, row[m])));
+        }
+        if depth == m + self.k {
+            return;
+        }
+        let mut next: Vec<usize> = vec![0; m + 1];
+        for c in &self.alphabet {
+            let (nsp, nep) = self.fmx.extend_left(*c, sp, ep);
+            if nsp >= nep {
+                continue;
+            }
+            next[0] = depth + 1;
+            for j in 1..=m {
+                let sub = row[j - 1] + usize::from(*c != self.pat[m - j]);
+                next[j] = std::cmp::min(sub, std::cmp::min(row[j], next[j - 1]) + 1);
+            }
+            if next.iter().min().unwrap() <= &self.k {
+                self.edit(&next, depth + 1, nsp, nep, hits);
+            }
+        }
+    }
+}
+
+/// Occurrences of pat with at most k mismatches, found by backtracking on
+/// the FM-index, as (position, mismatches) sorted by position. Branches
+/// are pruned as soon as they exceed k mismatches.
+///
+/// #Example
+///
+/// ```
+/// let txt = "MISSISSIPPI".to_string();
+/// let say = sufsort_rs::sufsort::SA::<i64>::new(txt.as_bytes());
+/// let fmx = sufsort_rs::fmindex::FMIndex::new(txt.as_bytes(), &say.sarray);
+/// let hits = sufsort_rs::approx::search_hamming(&fmx, "SIP".as_bytes(), 1);
+/// assert_eq!(hits, &[(3, 1), (6, 0)]);
+/// ```
+pub fn search_hamming<T>(fmx: &FMIndex<T>, pat: &[u8], k: usize) -> Vec<(usize, usize)>
+    where T: std::marker::Copy + num::ToPrimitive {
+    let mut hits: Vec<(usize, usize)> = Vec::new();
+    if pat.is_empty() {
+        return hits;
+    }
+    let search = Search{fmx, alphabet: fmx.alphabet(), pat, k};
+    search.hamming(pat.len(), 0, 0, fmx.rows(), &mut hits);
+    hits.sort_unstable();
+    hits
+}
+
+/// Occurrences of pat within edit distance k, as (position, length,
+/// distance) sorted by position. Each position is reported once, with the
+/// smallest distance of the non-empty substrings starting there, and the
+/// shortest of those substrings. Backtracking on the FM-index computes a
+/// column of the edit distance matrix per character, and a branch is pruned
+/// when the whole column exceeds k.
+///
+/// #Example
+///
+/// ```
+/// let txt = "MISSISSIPPI".to_string();
+/// let say = sufsort_rs::sufsort::SA::<i64>::new(txt.as_bytes());
+/// let fmx = sufsort_rs::fmindex::FMIndex::new(txt.as_bytes(), &say.sarray);
+/// let hits = sufsort_rs::approx::search_edit(&fmx, "ISSP".as_bytes(), 1);
+/// assert_eq!(hits, &[(1, 3, 1), (4, 3, 1)]);
+/// ```
+pub fn search_edit<T>(fmx: &FMIndex<T>, pat: &[u8], k: usize) -> Vec<(usize, usize, usize)>
+    where T: std::marker::Copy + num::ToPrimitive {
+    let mut hits: Vec<(usize, usize, usize)> = Vec::new();
+    if pat.is_empty() {
+        return hits;
+    }
+    let search = Search{fmx, alphabet: fmx.alphabet(), pat, k};
+    let row: Vec<usize> = (0..=pat.len()).collect();
+    search.edit(&row, 0, 0, fmx.rows(), &mut hits);
+    hits.sort_unstable_by_key(|h| (h.0, h.2, h.1));
+    hits.dedup_by_key(|h| h.0);
+    hits
+}
@@ -0,0 +1,186 @@
+use fmindex::FMIndex;
+
+/// Interval of a string w in a bidirectional FM-index : the rows
+/// [fwd, fwd + size) of the index of the text are prefixed by w, and the
+/// rows [rev, rev + size) of the index of the reversed text by the reverse
+/// of w.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BiInterval {
+    pub fwd: usize,
+    pub rev: usize,
+    pub size: usize,
+}
+
+impl BiInterval {
+    pub fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+}
+
+/// Bidirectional FM-index (2BWT) : FM-indexes of a text and of its reverse,
+/// with intervals kept synchronised so that a match can be extended by a
+/// character on either side.
+pub struct BiFMIndex<'s, T> {
+    pub fwd: FMIndex<'s, T>,
+    pub rev: FMIndex<'s, T>,
+    alphabet: Vec<u8>,
+}
+
+impl<'s, T> BiFMIndex<'s, T>
+    where T: std::marker::Copy + num::ToPrimitive {
+
+    /// Construct the index of txt, given the suffix arrays sa of txt and
+    /// rsa of its reverse.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::sufsort::SA;
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let rtxt: Vec<u8> = txt.bytes().rev().collect();
+    /// let (say, rsay) = (SA::<i64>::new(txt.as_bytes()), SA::<i64>::new(&rtxt));
+    /// let bfm = sufsort_rs::bifm::BiFMIndex::new(txt.as_bytes(), &say.sarray,
+    ///                                            &rtxt, &rsay.sarray);
+    /// // "SS" extended to "SSI" on the right, then to "ISSI" on the left
+    /// let iv = bfm.search("SS".as_bytes());
+    /// let iv = bfm.extend_right(b'I', iv);
+    /// let iv = bfm.extend_left(b'I', iv);
+    /// assert_eq!(bfm.locate(iv), &[1, 4]);
+    /// ```
+    pub fn new(txt: &[u8], sa: &'s [T], rtxt: &[u8], rsa: &'s [T]) -> Self {
+        assert!(txt.len() == rtxt.len() && txt.iter().rev().eq(rtxt.iter()));
+        let fwd = FMIndex::new(txt, sa);
+        let rev = FMIndex::new(rtxt, rsa);
+        let alphabet = fwd.alphabet();
+        BiFMIndex{fwd, rev, alphabet}
+    }
+
+    /// Length of the indexed text
+    pub fn len(&self) -> usize {
+        self.fwd.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fwd.is_empty()
+    }
+
+    /// Interval of the empty string, i.e., all the rows
+    pub fn full(&self) -> BiInterval {
+        BiInterval{fwd: 0, rev: 0, size: self.fwd.rows()}
+    }
+
+    // number of rows [sp, ep) of fmx whose BWT character is smaller than c,
+    // the terminator being the smallest
+    fn count_smaller(&self, fmx: &FMIndex<'s, T>, c: u8, sp: usize, ep: usize) -> usize {
+        let mut cx = usize::from(sp <= fmx.primary && fmx.primary < ep);
+        for a in self.alphabet.iter().take_while(|a| **a < c) {
+            cx += fmx.rank(*a, ep) - fmx.rank(*a, sp);
+        }
+        cx
+    }
+
+    /// Interval of c.w, given the interval iv of w
+    pub fn extend_left(&self, c: u8, iv: BiInterval) -> BiInterval {
+        let (sp, ep) = self.fwd.extend_left(c, iv.fwd, iv.fwd + iv.size);
+        if sp >= ep {
+            return BiInterval{fwd: sp, rev: iv.rev, size: 0};
+        }
+        let rev = iv.rev + self.count_smaller(&self.fwd, c, iv.fwd, iv.fwd + iv.size);
+        BiInterval{fwd: sp, rev, size: ep - sp}
+    }
+
+    /// Interval of w.c, given the interval iv of w
+    pub fn extend_right(&self, c: u8, iv: BiInterval) -> BiInterval {
+        let (sp, ep) = self.rev.extend_left(c, iv.rev, iv.rev + iv.size);
+        if sp >= ep {
+            return BiInterval{fwd: iv.fwd, rev: sp, size: 0};
+        }
+        let fwd = iv.fwd + self.count_smaller(&self.rev, c, iv.rev, iv.rev + iv.size);
+        BiInterval{fwd, rev: sp, size: ep - sp}
+    }
+
+    /// Interval of pat, matched from left to right
+    pub fn search(&self, pat: &[u8]) -> BiInterval {
+        let mut iv = self.full();
+        for c in pat {
+            iv = self.extend_right(*c, iv);
+            if iv.is_empty() {
+                break;
+            }
+        }
+        iv
+    }
+
+    /// Sorted text positions of the occurrences in iv
+    pub fn locate(&self, iv: BiInterval) -> Vec<usize> {
+        let mut occ: Vec<usize> = (iv.fwd..iv.fwd + iv.size)
+            .map(|r| self.fwd.locate(r)).collect();
+        occ.sort_unstable();
+        occ
+    }
+
+    // extend iv, the interval of pat[i..j], to the right with pat[j..], then
+    // to the left with pat[..i]
+    fn hamming_extend(&self, pat: &[u8], (i, j): (usize, usize), d: usize, k: usize,
+                      iv: BiInterval, hits: &mut Vec<(usize, usize)>) {
+        if j < pat.len() {
+            for c in &self.alphabet {
+                let dx = d + usize::from(*c != pat[j]);
+                let nv = self.extend_right(*c, iv);
+                if dx <= k && !nv.is_empty() {
+                    self.hamming_extend(pat, (i, j + 1), dx, k, nv, hits);
+                }
+            }
+        } else if i > 0 {
+            for c in &self.alphabet {
+                let dx = d + usize::from(*c != pat[i - 1]);
+                let nv = self.extend_left(*c, iv);
+                if dx <= k && !nv.is_empty() {
+                    self.hamming_extend(pat, (i - 1, j), dx, k, nv, hits);
+                }
+            }
+        } else {
+            hits.extend((iv.fwd..iv.fwd + iv.size).map(|r| (self.fwd.locate(r), d)));
+        }
+    }
+
+    /// Occurrences of pat with at most k mismatches, as (position,
+    /// mismatches) sorted by position, as approx::search_hamming. By the
+    /// pigeonhole principle, one of k + 1 parts of pat matches exactly; each
+    /// part is matched first, then extended with mismatches on both sides.
+    /// If k is at least the length of pat, the search starts from the empty
+    /// string instead.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::sufsort::SA;
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let rtxt: Vec<u8> = txt.bytes().rev().collect();
+    /// let (say, rsay) = (SA::<i64>::new(txt.as_bytes()), SA::<i64>::new(&rtxt));
+    /// let bfm = sufsort_rs::bifm::BiFMIndex::new(txt.as_bytes(), &say.sarray,
+    ///                                            &rtxt, &rsay.sarray);
+    /// assert_eq!(bfm.hamming("SIP".as_bytes(), 1), &[(3, 1), (6, 0)]);
+    /// ```
+    pub fn hamming(&self, pat: &[u8], k: usize) -> Vec<(usize, usize)> {
+        let m = pat.len();
+        let mut hits: Vec<(usize, usize)> = Vec::new();
+        if m == 0 {
+            return hits;
+        }
+        let seeds: Vec<(usize, usize)> = if k < m {
+            (0..=k).map(|p| (p * m / (k + 1), (p + 1) * m / (k + 1))).collect()
+        } else {
+            vec![(0, 0)]
+        };
+        for (i, j) in seeds {
+            let iv = self.search(&pat[i..j]);
+            if !iv.is_empty() {
+                self.hamming_extend(pat, (i, j), 0, k, iv, &mut hits);
+            }
+        }
+        hits.sort_unstable();
+        hits.dedup();
+        hits
+    }
+}
@@ -0,0 +1,121 @@
+// ranks are sampled every RANK_RATE bits, select every SELECT_RATE ones
+const RANK_RATE: usize = 512;
+const SELECT_RATE: usize = 256;
+
+/// Immutable bit vector with rank and select support. Ranks are sampled
+/// every 512 bits and the positions of every 256th set bit are sampled for
+/// select, which then scans the words from the closest sample.
+pub struct BitVector {
+    pub len: usize,
+    words: Vec<u64>,
+    // number of set bits before each rank block
+    ranks: Vec<usize>,
+    // word index of every SELECT_RATE-th set bit
+    samples: Vec<usize>,
+    ones: usize,
+}
+
+impl BitVector {
+    /// Bit vector of len bits stored in words, least significant bit first.
+    pub fn from_words(len: usize, mut words: Vec<u64>) -> Self {
+        assert!(words.len() * 64 >= len);
+        words.truncate(len.div_ceil(64));
+        if !len.is_multiple_of(64) {
+            let last = words.len() - 1;
+            words[last] &= (1u64 << (len % 64)) - 1;
+        }
+        let wpb = RANK_RATE / 64;
+        let mut ranks: Vec<usize> = Vec::with_capacity(words.len() / wpb + 1);
+        let mut samples: Vec<usize> = Vec::new();
+        let mut ones: usize = 0;
+        for (k, w) in words.iter().enumerate() {
+            if k.is_multiple_of(wpb) {
+                ranks.push(ones);
+            }
+            ones += w.count_ones() as usize;
+            // the sampled ones that fall in this word
+            while samples.len() * SELECT_RATE < ones {
+                samples.push(k);
+            }
+        }
+        ranks.push(ones);
+        BitVector{len, words, ranks, samples, ones}
+    }
+
+    /// Bit vector of len bits with the bits at the given positions set.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let bv = sufsort_rs::bits::BitVector::from_positions(100, vec![3, 64, 70]);
+    /// assert!(bv.get(64) && !bv.get(65));
+    /// assert_eq!(bv.rank1(65), 2);
+    /// assert_eq!(bv.select1(2), 70);
+    /// ```
+    pub fn from_positions<I>(len: usize, positions: I) -> Self
+        where I: IntoIterator<Item=usize> {
+        let mut words: Vec<u64> = vec![0; len.div_ceil(64)];
+        for p in positions {
+            assert!(p < len);
+            words[p / 64] |= 1u64 << (p % 64);
+        }
+        BitVector::from_words(len, words)
+    }
+
+    /// Value of the bit i
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len);
+        (self.words[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    /// Number of set bits
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Number of set bits in [0, i)
+    pub fn rank1(&self, i: usize) -> usize {
+        assert!(i <= self.len);
+        let wpb = RANK_RATE / 64;
+        let b = i / RANK_RATE;
+        let mut rx = self.ranks[b];
+        for w in self.words[b * wpb..i / 64].iter() {
+            rx += w.count_ones() as usize;
+        }
+        if !i.is_multiple_of(64) {
+            rx += (self.words[i / 64] & ((1u64 << (i % 64)) - 1)).count_ones() as usize;
+        }
+        rx
+    }
+
+    /// Position of the k-th set bit, counting from 0
+    pub fn select1(&self, k: usize) -> usize {
+        assert!(k < self.ones);
+        let mut w = self.samples[k / SELECT_RATE];
+        // number of set bits before the word w
+        let wpb = RANK_RATE / 64;
+        let mut rx = self.ranks[w / wpb];
+        for x in self.words[(w / wpb) * wpb..w].iter() {
+            rx += x.count_ones() as usize;
+        }
+        loop {
+            let c = self.words[w].count_ones() as usize;
+            if rx + c > k {
+                break;
+            }
+            rx += c;
+            w += 1;
+        }
+        // clear the lower set bits of the word
+        let mut x = self.words[w];
+        for _ in 0..(k - rx) {
+            x &= x - 1;
+        }
+        w * 64 + x.trailing_zeros() as usize
+    }
+
+    /// Size of the bit vector and its samples in bytes
+    pub fn size_in_bytes(&self) -> usize {
+        8 * (self.words.len() + self.ranks.len() + self.samples.len())
+    }
+}
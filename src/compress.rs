@@ -0,0 +1,370 @@
+use std::io::{Read, Write, Error, ErrorKind};
+use sufsort::{BWT, inverse_bwt};
+
+/// Default size of the blocks of input that are sorted independently
+pub const DEFAULT_BLOCK_SIZE: usize = 900_000;
+
+const MAGIC: &[u8; 4] = b"SSBW";
+const VERSION: u8 = 1;
+// symbols after zero-run-length coding : RUNA and RUNB encode runs of zeros
+// in bijective base 2, MTF value v > 0 is v + 1, and EOB ends the block
+const RUNA: usize = 0;
+const RUNB: usize = 1;
+const EOB: usize = 257;
+const NSYMBOLS: usize = 258;
+const MAX_CODE_LEN: u8 = 20;
+
+/// Compress everything read from reader into writer with the default block
+/// size.
+///
+/// #Example
+///
+/// ```
+/// let txt = "MISSISSIPPI MISSISSIPPI MISSISSIPPI".to_string();
+/// let mut packed: Vec<u8> = Vec::new();
+/// sufsort_rs::compress::compress(txt.as_bytes(), &mut packed).unwrap();
+/// let mut unpacked: Vec<u8> = Vec::new();
+/// sufsort_rs::compress::decompress(&packed[..], &mut unpacked).unwrap();
+/// assert_eq!(unpacked, txt.as_bytes());
+/// ```
+pub fn compress<R: Read, W: Write>(reader: R, writer: W) -> std::io::Result<()> {
+    compress_blocks(reader, writer, DEFAULT_BLOCK_SIZE)
+}
+
+/// Compress everything read from reader into writer, sorting blocks of
+/// block_size bytes independently with BWT::new.
+///
+/// The output is the magic "SSBW", a version byte and the block size as a
+/// u32, followed by the blocks, each framed as its length, its BWT idx, the
+/// Huffman code lengths of its symbols and the byte length of its coded bits.
+/// A block of length 0 ends the stream. All the integers are u32
+/// little-endian.
+pub fn compress_blocks<R: Read, W: Write>(mut reader: R, mut writer: W,
+                                          block_size: usize) -> std::io::Result<()> {
+    assert!(block_size > 0 && block_size < i32::MAX as usize);
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION])?;
+    write_u32(&mut writer, block_size)?;
+    let mut block: Vec<u8> = Vec::with_capacity(block_size);
+    loop {
+        block.clear();
+        (&mut reader).take(block_size as u64).read_to_end(&mut block)?;
+        if block.is_empty() {
+            break;
+        }
+        write_block(&mut writer, &block)?;
+    }
+    write_u32(&mut writer, 0)?;
+    writer.flush()
+}
+
+/// Decompress a stream written by compress into writer.
+pub fn decompress<R: Read, W: Write>(mut reader: R, mut writer: W) -> std::io::Result<()> {
+    let mut magic: [u8; 5] = [0; 5];
+    reader.read_exact(&mut magic)?;
+    if &magic[..4] != MAGIC || magic[4] != VERSION {
+        return Err(invalid("not a compressed stream"));
+    }
+    let block_size = read_u32(&mut reader)?;
+    loop {
+        let len = read_u32(&mut reader)?;
+        if len == 0 {
+            break;
+        }
+        if len > block_size {
+            return Err(invalid("block larger than the block size"));
+        }
+        let block = read_block(&mut reader, len)?;
+        writer.write_all(&block)?;
+    }
+    writer.flush()
+}
+
+fn invalid(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+fn write_u32<W: Write>(writer: &mut W, x: usize) -> std::io::Result<()> {
+    writer.write_all(&(x as u32).to_le_bytes())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> std::io::Result<usize> {
+    let mut buf: [u8; 4] = [0; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf) as usize)
+}
+
+fn write_block<W: Write>(writer: &mut W, block: &[u8]) -> std::io::Result<()> {
+    let btx = BWT::<i32>::new(block);
+    let symbols = rle_encode(&mtf_encode(&btx.bwt));
+    let mut freq: Vec<usize> = vec![0; NSYMBOLS];
+    for s in symbols.iter() {
+        freq[*s] += 1;
+    }
+    let lengths = code_lengths(&freq);
+    let codes = canonical_codes(&lengths);
+    let mut bits = BitWriter::new();
+    for s in symbols.iter() {
+        bits.write(codes[*s], lengths[*s]);
+    }
+    let payload = bits.finish();
+
+    write_u32(writer, block.len())?;
+    write_u32(writer, btx.idx)?;
+    writer.write_all(&lengths)?;
+    write_u32(writer, payload.len())?;
+    writer.write_all(&payload)
+}
+
+fn read_block<R: Read>(reader: &mut R, len: usize) -> std::io::Result<Vec<u8>> {
+    let idx = read_u32(reader)?;
+    let mut lengths: Vec<u8> = vec![0; NSYMBOLS];
+    reader.read_exact(&mut lengths)?;
+    if idx >= len || lengths.iter().any(|l| *l > MAX_CODE_LEN) {
+        return Err(invalid("corrupt block header"));
+    }
+    // the payload length is not trusted : the buffer only grows with the
+    // bytes actually read
+    let plen = read_u32(reader)?;
+    let mut payload: Vec<u8> = Vec::new();
+    reader.take(plen as u64).read_to_end(&mut payload)?;
+    if payload.len() != plen {
+        return Err(invalid("truncated block data"));
+    }
+
+    let decoder = Decoder::new(&lengths);
+    let mut bits = BitReader{data: &payload, pos: 0};
+    let mut symbols: Vec<usize> = Vec::new();
+    loop {
+        let s = decoder.decode(&mut bits).ok_or_else(|| invalid("corrupt block data"))?;
+        if s == EOB {
+            break;
+        }
+        symbols.push(s);
+    }
+    let bwt = mtf_decode(&rle_decode(&symbols, len)?);
+    if bwt.len() != len {
+        return Err(invalid("corrupt block data"));
+    }
+    Ok(inverse_bwt(&bwt, idx))
+}
+
+// move-to-front coding over the byte alphabet
+fn mtf_encode(src: &[u8]) -> Vec<u8> {
+    let mut order: Vec<u8> = (0..=255).collect();
+    src.iter().map(|c| {
+        let k = order.iter().position(|x| x == c).unwrap();
+        order[..k + 1].rotate_right(1);
+        k as u8
+    }).collect()
+}
+
+fn mtf_decode(src: &[u8]) -> Vec<u8> {
+    let mut order: Vec<u8> = (0..=255).collect();
+    src.iter().map(|k| {
+        let k = *k as usize;
+        let c = order[k];
+        order[..k + 1].rotate_right(1);
+        c
+    }).collect()
+}
+
+// zero-run-length coding of MTF values, terminated by EOB
+fn rle_encode(src: &[u8]) -> Vec<usize> {
+    let mut dst: Vec<usize> = Vec::new();
+    let mut zeros: usize = 0;
+    let flush = |dst: &mut Vec<usize>, zeros: &mut usize| {
+        // run length in bijective base 2, least significant digit first
+        while *zeros > 0 {
+            *zeros -= 1;
+            dst.push(if *zeros & 1 == 0 { RUNA } else { RUNB });
+            *zeros >>= 1;
+        }
+    };
+    for v in src.iter() {
+        if *v == 0 {
+            zeros += 1;
+        } else {
+            flush(&mut dst, &mut zeros);
+            dst.push(*v as usize + 1);
+        }
+    }
+    flush(&mut dst, &mut zeros);
+    dst.push(EOB);
+    dst
+}
+
+fn rle_decode(src: &[usize], max_len: usize) -> std::io::Result<Vec<u8>> {
+    let mut dst: Vec<u8> = Vec::new();
+    let mut run: usize = 0;
+    let mut weight: usize = 1;
+    for s in src.iter() {
+        if *s == RUNA || *s == RUNB {
+            run += weight * (*s + 1);
+            weight <<= 1;
+            if run > max_len {
+                return Err(invalid("corrupt block data"));
+            }
+        } else {
+            dst.resize(dst.len() + run, 0);
+            run = 0;
+            weight = 1;
+            dst.push((*s - 1) as u8);
+        }
+    }
+    dst.resize(dst.len() + run, 0);
+    Ok(dst)
+}
+
+// Huffman code lengths of the symbols with non-zero frequency, limited to
+// MAX_CODE_LEN bits by flattening the frequencies until they fit.
+fn code_lengths(freq: &[usize]) -> Vec<u8> {
+    let mut freq: Vec<usize> = freq.to_vec();
+    loop {
+        let lengths = huffman_lengths(&freq);
+        if lengths.iter().all(|l| *l <= MAX_CODE_LEN) {
+            return lengths;
+        }
+        for f in freq.iter_mut().filter(|f| **f > 0) {
+            *f = *f / 2 + 1;
+        }
+    }
+}
+
+fn huffman_lengths(freq: &[usize]) -> Vec<u8> {
+    use std::collections::BinaryHeap;
+    use std::cmp::Reverse;
+    let mut lengths: Vec<u8> = vec![0; freq.len()];
+    // nodes : leaves are the symbols, internal nodes point to their parent
+    let mut parent: Vec<usize> = Vec::new();
+    let mut heap: BinaryHeap<Reverse<(usize, usize)>> = BinaryHeap::new();
+    let mut leaves: Vec<(usize, usize)> = Vec::new();
+    for (s, f) in freq.iter().enumerate().filter(|x| *x.1 > 0) {
+        leaves.push((s, parent.len()));
+        heap.push(Reverse((*f, parent.len())));
+        parent.push(usize::MAX);
+    }
+    if leaves.len() == 1 {
+        lengths[leaves[0].0] = 1;
+        return lengths;
+    }
+    while heap.len() > 1 {
+        let Reverse((f1, n1)) = heap.pop().unwrap();
+        let Reverse((f2, n2)) = heap.pop().unwrap();
+        let nx = parent.len();
+        parent.push(usize::MAX);
+        parent[n1] = nx;
+        parent[n2] = nx;
+        heap.push(Reverse((f1 + f2, nx)));
+    }
+    for (s, node) in leaves {
+        let mut depth: usize = 0;
+        let mut x = node;
+        while parent[x] != usize::MAX {
+            x = parent[x];
+            depth += 1;
+        }
+        lengths[s] = std::cmp::min(depth, u8::MAX as usize) as u8;
+    }
+    lengths
+}
+
+// canonical Huffman codes : by increasing length, then by symbol
+fn canonical_codes(lengths: &[u8]) -> Vec<u32> {
+    let mut codes: Vec<u32> = vec![0; lengths.len()];
+    let mut code: u32 = 0;
+    for l in 1..(MAX_CODE_LEN + 1) {
+        for (s, x) in lengths.iter().enumerate() {
+            if *x == l {
+                codes[s] = code;
+                code += 1;
+            }
+        }
+        code <<= 1;
+    }
+    codes
+}
+
+struct Decoder {
+    // first[l] : first canonical code of length l, count[l] : number of
+    // codes of length l, offset[l] : index in symbols of the first of them
+    first: Vec<u32>,
+    count: Vec<u32>,
+    offset: Vec<usize>,
+    symbols: Vec<usize>,
+}
+
+impl Decoder {
+    fn new(lengths: &[u8]) -> Decoder {
+        let nl = MAX_CODE_LEN as usize + 1;
+        let mut dx = Decoder{first: vec![0; nl], count: vec![0; nl],
+                             offset: vec![0; nl], symbols: Vec::new()};
+        let mut code: u32 = 0;
+        for l in 1..nl {
+            dx.first[l] = code;
+            dx.offset[l] = dx.symbols.len();
+            for (s, x) in lengths.iter().enumerate() {
+                if *x as usize == l {
+                    dx.symbols.push(s);
+                    dx.count[l] += 1;
+                }
+            }
+            code = (code + dx.count[l]) << 1;
+        }
+        dx
+    }
+
+    fn decode(&self, bits: &mut BitReader) -> Option<usize> {
+        let mut code: u32 = 0;
+        for l in 1..self.first.len() {
+            code = (code << 1) | bits.read()?;
+            if code.wrapping_sub(self.first[l]) < self.count[l] {
+                return Some(self.symbols[self.offset[l] + (code - self.first[l]) as usize]);
+            }
+        }
+        None
+    }
+}
+
+// bits are packed most significant first
+struct BitWriter {
+    data: Vec<u8>,
+    acc: u64,
+    nbits: u32,
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter{data: Vec::new(), acc: 0, nbits: 0}
+    }
+
+    fn write(&mut self, code: u32, len: u8) {
+        self.acc = (self.acc << len) | code as u64;
+        self.nbits += len as u32;
+        while self.nbits >= 8 {
+            self.nbits -= 8;
+            self.data.push((self.acc >> self.nbits) as u8);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.nbits > 0 {
+            self.data.push((self.acc << (8 - self.nbits)) as u8);
+        }
+        self.data
+    }
+}
+
+struct BitReader<'s> {
+    data: &'s [u8],
+    pos: usize,
+}
+
+impl<'s> BitReader<'s> {
+    fn read(&mut self) -> Option<u32> {
+        let byte = self.data.get(self.pos / 8)?;
+        let bit = (byte >> (7 - self.pos % 8)) & 1;
+        self.pos += 1;
+        Some(bit as u32)
+    }
+}
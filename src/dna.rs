@@ -0,0 +1,247 @@
+use packed::PackedSA;
+use sufsort::{SA, SuffixArray, search_by};
+
+/// Codes of the reduced DNA alphabet. All the IUPAC ambiguity codes are
+/// mapped to N, which sorts after T.
+pub const A: u8 = 0;
+pub const C: u8 = 1;
+pub const G: u8 = 2;
+pub const T: u8 = 3;
+pub const N: u8 = 4;
+
+const LETTERS: [u8; 5] = [b'A', b'C', b'G', b'T', b'N'];
+
+/// Code of the nucleotide c in either case, with U read as T and the IUPAC
+/// ambiguity codes (RYSWKMBDHVN) read as N, as well as the gap symbols '-'
+/// and '.' and the stop or masked symbol '*' found in alignments. None if c
+/// is none of those. Soft-masked (lower case) regions are thus folded into
+/// upper case, and the mask is lost.
+pub fn encode(c: u8) -> Option<u8> {
+    match c.to_ascii_uppercase() {
+        b'A' => Some(A),
+        b'C' => Some(C),
+        b'G' => Some(G),
+        b'T' | b'U' => Some(T),
+        b'R' | b'Y' | b'S' | b'W' | b'K' | b'M' |
+        b'B' | b'D' | b'H' | b'V' | b'N' |
+        b'-' | b'.' | b'*' => Some(N),
+        _ => None,
+    }
+}
+
+/// Upper case letter of the code x
+pub fn decode(x: u8) -> u8 {
+    LETTERS[x as usize]
+}
+
+/// IUPAC complement of c, preserving its case. Other characters are left
+/// as is.
+pub fn complement(c: u8) -> u8 {
+    let x = match c.to_ascii_uppercase() {
+        b'A' => b'T', b'T' | b'U' => b'A', b'C' => b'G', b'G' => b'C',
+        b'R' => b'Y', b'Y' => b'R', b'K' => b'M', b'M' => b'K',
+        b'B' => b'V', b'V' => b'B', b'D' => b'H', b'H' => b'D',
+        _ => return c,
+    };
+    if c.is_ascii_lowercase() { x.to_ascii_lowercase() } else { x }
+}
+
+/// IUPAC reverse complement of seq
+///
+/// #Example
+///
+/// ```
+/// let rc = sufsort_rs::dna::reverse_complement("ACGTNRyk".as_bytes());
+/// assert_eq!(rc, "mrYNACGT".as_bytes());
+/// ```
+pub fn reverse_complement(seq: &[u8]) -> Vec<u8> {
+    seq.iter().rev().map(|c| complement(*c)).collect()
+}
+
+/// DNA sequence with ACGT packed in 2 bits. The positions holding N (or
+/// any ambiguity code) are kept as a sorted list of runs, which is small
+/// for assembled genomes.
+pub struct PackedDNA {
+    len: usize,
+    words: Vec<u64>,
+    // (start, length) of the runs of N
+    n_runs: Vec<(usize, usize)>,
+}
+
+impl PackedDNA {
+    /// Pack seq, with the codes of encode : lower case is folded to upper
+    /// case, and gaps are stored as N. Panics if seq contains characters
+    /// that encode rejects.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let dna = sufsort_rs::dna::PackedDNA::new("ACGTNNRacgu-*".as_bytes());
+    /// assert_eq!(dna.unpack(), "ACGTNNNACGTNN".as_bytes());
+    /// assert_eq!(dna.n_runs(), &[(4, 3), (11, 2)]);
+    /// ```
+    pub fn new(seq: &[u8]) -> Self {
+        let mut words: Vec<u64> = vec![0; seq.len().div_ceil(32)];
+        let mut n_runs: Vec<(usize, usize)> = Vec::new();
+        for (i, c) in seq.iter().enumerate() {
+            match encode(*c) {
+                Some(N) => match n_runs.last_mut() {
+                    Some(r) if r.0 + r.1 == i => r.1 += 1,
+                    _ => n_runs.push((i, 1)),
+                },
+                Some(x) => words[i / 32] |= (x as u64) << (2 * (i % 32)),
+                None => panic!("PackedDNA: {:?} is not a nucleotide code", *c as char),
+            }
+        }
+        PackedDNA{len: seq.len(), words, n_runs}
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Runs of N as (start, length)
+    pub fn n_runs(&self) -> &[(usize, usize)] {
+        &self.n_runs
+    }
+
+    /// Code of the nucleotide at position i
+    pub fn get(&self, i: usize) -> u8 {
+        assert!(i < self.len);
+        let k = self.n_runs.partition_point(|r| r.0 <= i);
+        if k > 0 && i < self.n_runs[k - 1].0 + self.n_runs[k - 1].1 {
+            N
+        } else {
+            ((self.words[i / 32] >> (2 * (i % 32))) & 3) as u8
+        }
+    }
+
+    /// Codes of all the positions
+    pub fn to_codes(&self) -> Vec<u8> {
+        let mut codes: Vec<u8> = (0..self.len).map(|i| {
+            ((self.words[i / 32] >> (2 * (i % 32))) & 3) as u8
+        }).collect();
+        for &(start, length) in &self.n_runs {
+            for x in &mut codes[start..start + length] {
+                *x = N;
+            }
+        }
+        codes
+    }
+
+    /// Upper case ACGTN sequence
+    pub fn unpack(&self) -> Vec<u8> {
+        self.to_codes().into_iter().map(decode).collect()
+    }
+
+    pub fn size_in_bytes(&self) -> usize {
+        8 * self.words.len() + 16 * self.n_runs.len()
+    }
+}
+
+/// Strand of an occurrence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Strand {
+    Forward,
+    Reverse,
+}
+
+/// Suffix array index of a DNA sequence, over the reduced alphabet ACGTN,
+/// with the text packed in 2 bits and the SA in ceil(log2(n+1)) bits.
+pub struct DnaIndex {
+    pub text: PackedDNA,
+    pub sarray: PackedSA,
+}
+
+impl DnaIndex {
+    /// Construct the index of seq. The SA is sorted over the codes, so that N
+    /// sorts after T. The construction temporarily uses one byte per
+    /// character for the codes and an i32 SA, or an i64 SA from 2^31
+    /// characters on, which peaks at about 5n bytes, or 9n bytes, on top
+    /// of the index.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::dna::{DnaIndex, Strand};
+    /// let dix = DnaIndex::new("ACGTTAGCAnGT".as_bytes());
+    /// assert_eq!(dix.count("GT".as_bytes()), 2);
+    /// assert_eq!(dix.locate_both("ACG".as_bytes()),
+    ///            &[(0, Strand::Forward), (1, Strand::Reverse)]);
+    /// ```
+    pub fn new(seq: &[u8]) -> Self {
+        let text = PackedDNA::new(seq);
+        let codes = text.to_codes();
+        let sarray = if codes.len() <= i32::MAX as usize {
+            PackedSA::from_sa(&SA::<i32>::new(&codes).sarray)
+        } else {
+            PackedSA::from_sa(&SA::<i64>::new(&codes).sarray)
+        };
+        DnaIndex{text, sarray}
+    }
+
+    pub fn len(&self) -> usize {
+        self.text.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// BWT over the letters ACGTN, wrapping around as BWT::new, with the
+    /// row of the suffix starting at 0.
+    pub fn bwt(&self) -> (Vec<u8>, usize) {
+        let n = self.len();
+        let mut idx: usize = 0;
+        let bwt: Vec<u8> = self.sarray.iter().enumerate().map(|(i, p)| {
+            if p == 0 {
+                idx = i;
+            }
+            decode(self.text.get(if p == 0 { n - 1 } else { p - 1 }))
+        }).collect();
+        (bwt, idx)
+    }
+
+    /// Range (left, count) of the SA holding the occurrences of pat. Only
+    /// ACGT (or U) match: a pattern with an ambiguity code has no
+    /// occurrences, as N never matches.
+    pub fn search(&self, pat: &[u8]) -> (usize, usize) {
+        let mut codes: Vec<u8> = Vec::with_capacity(pat.len());
+        for c in pat {
+            match encode(*c) {
+                Some(x) if x != N => codes.push(x),
+                _ => return (0, 0),
+            }
+        }
+        let n = self.len();
+        search_by(&self.sarray, &codes, |p| if p < n { Some(self.text.get(p)) } else { None })
+    }
+
+    pub fn count(&self, pat: &[u8]) -> usize {
+        self.search(pat).1
+    }
+
+    /// Sorted positions of the occurrences of pat
+    pub fn locate(&self, pat: &[u8]) -> Vec<usize> {
+        let (left, count) = self.search(pat);
+        let mut occ: Vec<usize> = (left..left + count).map(|k| self.sarray.at(k)).collect();
+        occ.sort_unstable();
+        occ
+    }
+
+    /// Occurrences of pat on both strands, as (position, strand) sorted by
+    /// position. A reverse occurrence at p means that the reverse complement
+    /// of pat occurs at p on the forward strand.
+    pub fn locate_both(&self, pat: &[u8]) -> Vec<(usize, Strand)> {
+        let mut occ: Vec<(usize, Strand)> = self.locate(pat).into_iter()
+            .map(|p| (p, Strand::Forward)).collect();
+        occ.extend(self.locate(&reverse_complement(pat)).into_iter()
+                   .map(|p| (p, Strand::Reverse)));
+        occ.sort_unstable();
+        occ
+    }
+}
@@ -0,0 +1,158 @@
+use lyndon::lyndon_factorization;
+
+/// Extended BWT (Mantaci et al., 2007) of a multiset of strings : the last
+/// characters of all the rotations of all the strings, sorted in ω-order,
+/// i.e., by comparing the infinite repetitions of the rotations. No
+/// separators are needed between the strings.
+///
+/// starts[k] is the row of strings[k] itself and lengths[k] is its length,
+/// which is enough to restore the strings exactly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EBWT {
+    pub bwt: Vec<u8>,
+    pub starts: Vec<usize>,
+    pub lengths: Vec<usize>,
+}
+
+impl EBWT {
+    /// Construct the eBWT of the given non-empty strings by prefix doubling
+    /// over the rotations. Two infinite repetitions u^ω and v^ω are equal iff
+    /// they share a prefix of length |u| + |v|, so doubling stops at twice
+    /// the length of the longest string.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let strings: Vec<&[u8]> = vec!["ab".as_bytes(), "b".as_bytes()];
+    /// let ebx = sufsort_rs::ebwt::EBWT::new(&strings);
+    /// assert_eq!(ebx.bwt, "bab".as_bytes());
+    /// assert_eq!(ebx.inverse(), &["ab".as_bytes(), "b".as_bytes()]);
+    /// ```
+    pub fn new(strings: &[&[u8]]) -> Self {
+        let lengths: Vec<usize> = strings.iter().map(|x| x.len()).collect();
+        assert!(lengths.iter().all(|l| *l > 0));
+        let mut offsets: Vec<usize> = Vec::with_capacity(strings.len());
+        // string id of each rotation
+        let mut owner: Vec<usize> = Vec::new();
+        let mut rank: Vec<usize> = Vec::new();
+        for (k, s) in strings.iter().enumerate() {
+            offsets.push(owner.len());
+            owner.extend(std::iter::repeat_n(k, s.len()));
+            rank.extend(s.iter().map(|c| *c as usize));
+        }
+        let nrot = owner.len();
+        let max_len = lengths.iter().cloned().max().unwrap_or(0);
+        // rotation starting h characters after the rotation g
+        let shift = |g: usize, h: usize| -> usize {
+            let (o, l) = (offsets[owner[g]], lengths[owner[g]]);
+            o + (g - o + h) % l
+        };
+
+        let mut order: Vec<usize> = (0..nrot).collect();
+        order.sort_by_key(|&g| rank[g]);
+        let mut h: usize = 1;
+        while h < 2 * max_len {
+            let key = |g: usize| (rank[g], rank[shift(g, h)]);
+            order.sort_by_key(|&g| key(g));
+            let mut next: Vec<usize> = vec![0; nrot];
+            for i in 1..nrot {
+                next[order[i]] = next[order[i - 1]] +
+                    if key(order[i]) != key(order[i - 1]) { 1 } else { 0 };
+            }
+            rank = next;
+            h *= 2;
+        }
+
+        let mut bwt: Vec<u8> = Vec::with_capacity(nrot);
+        let mut starts: Vec<usize> = vec![0; strings.len()];
+        for (row, &g) in order.iter().enumerate() {
+            let k = owner[g];
+            let j = g - offsets[k];
+            if j == 0 {
+                starts[k] = row;
+            }
+            bwt.push(strings[k][(j + lengths[k] - 1) % lengths[k]]);
+        }
+        EBWT{bwt, starts, lengths}
+    }
+
+    /// Restore the strings, in their original order.
+    pub fn inverse(&self) -> Vec<Vec<u8>> {
+        let lf = lf_mapping(&self.bwt);
+        self.starts.iter().zip(self.lengths.iter()).map(|(&s, &l)| {
+            let mut r = s;
+            let mut sx: Vec<u8> = Vec::with_capacity(l);
+            for _ in 0..l {
+                sx.push(self.bwt[r]);
+                r = lf[r];
+            }
+            sx.reverse();
+            sx
+        }).collect()
+    }
+}
+
+// LF-mapping of a BWT : the row of the rotation starting with bwt[r], that
+// precedes the rotation at row r.
+fn lf_mapping(bwt: &[u8]) -> Vec<usize> {
+    let mut counts: [usize; 257] = [0; 257];
+    for c in bwt.iter() {
+        counts[*c as usize + 1] += 1;
+    }
+    for c in 0..256 {
+        counts[c + 1] += counts[c];
+    }
+    bwt.iter().map(|c| {
+        let r = counts[*c as usize];
+        counts[*c as usize] += 1;
+        r
+    }).collect()
+}
+
+/// Bijective BWT of text (Gil and Scott, 2012) : the eBWT of the Lyndon
+/// factors of text. Unlike the BWT, it needs neither a sentinel nor a
+/// primary index to be inverted.
+///
+/// #Example
+///
+/// ```
+/// let bbx = sufsort_rs::ebwt::bijective_bwt("banana".as_bytes());
+/// assert_eq!(bbx, "annbaa".as_bytes());
+/// assert_eq!(sufsort_rs::ebwt::inverse_bijective_bwt(&bbx), "banana".as_bytes());
+/// ```
+pub fn bijective_bwt(text: &[u8]) -> Vec<u8> {
+    let factors: Vec<&[u8]> = lyndon_factorization(text).into_iter()
+        .map(|(s, l)| &text[s..s + l]).collect();
+    EBWT::new(&factors).bwt
+}
+
+/// Inverse of the bijective BWT. Each cycle of the LF-mapping spells a Lyndon
+/// factor (or a power of one) read from its smallest row, and the text is the
+/// concatenation of these words in non-increasing order.
+pub fn inverse_bijective_bwt(bbwt: &[u8]) -> Vec<u8> {
+    let lf = lf_mapping(bbwt);
+    let mut visited: Vec<bool> = vec![false; bbwt.len()];
+    let mut words: Vec<Vec<u8>> = Vec::new();
+    for r0 in 0..bbwt.len() {
+        if visited[r0] {
+            continue;
+        }
+        let mut wx: Vec<u8> = Vec::new();
+        let mut r = r0;
+        while !visited[r] {
+            visited[r] = true;
+            wx.push(bbwt[r]);
+            r = lf[r];
+        }
+        wx.reverse();
+        words.push(wx);
+    }
+    // non-increasing ω-order, which is the lexicographic order for Lyndon
+    // words and is unchanged by taking powers
+    words.sort_by(|a, b| {
+        let ab: Vec<u8> = a.iter().chain(b.iter()).cloned().collect();
+        let ba: Vec<u8> = b.iter().chain(a.iter()).cloned().collect();
+        ba.cmp(&ab)
+    });
+    words.concat()
+}
@@ -0,0 +1,332 @@
+use sufsort::{SA, construct_isa};
+use lcp::construct_lcp_phi;
+use rmq::RMQ;
+
+/// Enhanced suffix array (Abouelhoda, Kurtz and Ohlebusch, 2004) : the suffix
+/// array, the LCP array and the child table, which together emulate the
+/// suffix tree of txt. Internal nodes of the suffix tree are the lcp-intervals
+/// [l..r] (inclusive) and leaves are the singleton intervals [i..i].
+///
+/// The child table is stored as three arrays up, down and next_l. Entries
+/// that are undefined are set to txt.len().
+///
+/// Note that txt is not terminated by a unique sentinel, so a suffix that is
+/// a prefix of another suffix ends inside the tree, and if all the suffixes
+/// share a common prefix, the root has a non-zero string depth.
+pub struct EnhancedSA<'s, T>{
+    pub txt: &'s [u8],
+    pub sarray: Vec<T>,
+    pub lcp: Vec<T>,
+    pub up: Vec<usize>,
+    pub down: Vec<usize>,
+    pub next_l: Vec<usize>,
+    // first l-index of the root interval
+    root_lindex: usize,
+}
+
+impl<'s> EnhancedSA<'s, i32> {
+    /// Constructs the enhanced suffix array for the given slice of u8 chars.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let esa = sufsort_rs::esa::EnhancedSA::<i32>::new(txt.as_bytes());
+    /// assert_eq!(esa.children(0, 10),
+    ///            &[(0, 3), (4, 4), (5, 6), (7, 10)]);
+    /// ```
+    pub fn new(src: &'s [u8]) -> Self {
+        let say = SA::<i32>::new(src);
+        EnhancedSA::from_sa(src, say.sarray)
+    }
+}
+
+impl<'s> EnhancedSA<'s, i64> {
+    /// Constructs the enhanced suffix array for the given slice of u8 chars.
+    pub fn new(src: &'s [u8]) -> Self {
+        let say = SA::<i64>::new(src);
+        EnhancedSA::from_sa(src, say.sarray)
+    }
+}
+
+impl<'s, T> EnhancedSA<'s, T>
+    where T: std::clone::Clone + std::marker::Copy +
+             std::ops::Add + std::ops::Sub<Output=T> +
+             std::cmp::Ord +
+             num::ToPrimitive + num::FromPrimitive + num::One + num::Zero {
+
+    /// Constructs the enhanced suffix array from an already constructed suffix
+    /// array sa of src.
+    pub fn from_sa(src: &'s [u8], sa: Vec<T>) -> Self {
+        let n = src.len();
+        assert!(n == sa.len());
+        let lcp: Vec<T> = if n > 0 {
+            construct_lcp_phi(src, &sa)
+        } else {
+            Vec::new()
+        };
+        // lcp value at i, with lcp[n] = -1 to close all the intervals
+        let lv = |i: usize| -> i64 {
+            if i < n { lcp[i].to_i64().unwrap() } else { -1 }
+        };
+
+        // (1) up and down values
+        let mut up: Vec<usize> = vec![n; n];
+        let mut down: Vec<usize> = vec![n; n];
+        let mut last: usize = n;
+        let mut stack: Vec<usize> = vec![0];
+        for i in 1..(n + 1) {
+            while let Some(&top) = stack.last() {
+                if lv(i) >= lv(top) {
+                    break;
+                }
+                last = top;
+                stack.pop();
+                if let Some(&ntop) = stack.last() {
+                    if lv(i) <= lv(ntop) && lv(ntop) != lv(last) {
+                        down[ntop] = last;
+                    }
+                }
+            }
+            if last != n {
+                if let Some(x) = up.get_mut(i) {
+                    *x = last;
+                }
+                last = n;
+            }
+            stack.push(i);
+        }
+
+        // (2) next l-index values
+        let mut next_l: Vec<usize> = vec![n; n];
+        stack.clear();
+        stack.push(0);
+        for i in 1..n {
+            while lv(i) < lv(*stack.last().unwrap()) {
+                stack.pop();
+            }
+            if lv(i) == lv(*stack.last().unwrap()) {
+                let top = stack.pop().unwrap();
+                next_l[top] = i;
+            }
+            stack.push(i);
+        }
+
+        // (3) first l-index of the root : the left-most minimum lcp value
+        let mut root_lindex = n;
+        for i in 1..n {
+            if root_lindex == n || lv(i) < lv(root_lindex) {
+                root_lindex = i;
+            }
+        }
+
+        EnhancedSA{txt: src, sarray: sa, lcp, up, down, next_l, root_lindex}
+    }
+
+    /// Number of suffixes
+    pub fn len(&self) -> usize {
+        self.txt.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.txt.is_empty()
+    }
+
+    /// Interval of the root of the suffix tree, i.e., [0..n-1]
+    pub fn root(&self) -> (usize, usize) {
+        (0, self.txt.len().saturating_sub(1))
+    }
+
+    // first l-index of the lcp-interval [l..r], l < r
+    fn first_lindex(&self, l: usize, r: usize) -> usize {
+        let n = self.txt.len();
+        if l == 0 && r + 1 == n {
+            self.root_lindex
+        } else if r + 1 < n && l < self.up[r + 1] && self.up[r + 1] <= r {
+            self.up[r + 1]
+        } else {
+            self.down[l]
+        }
+    }
+
+    /// Length of the string spelled from the root to the node [l..r]. For a
+    /// leaf [i..i], it is the length of the suffix SA[i].
+    pub fn string_depth(&self, l: usize, r: usize) -> usize {
+        if l == r {
+            self.txt.len() - self.sarray[l].to_usize().unwrap()
+        } else {
+            self.lcp[self.first_lindex(l, r)].to_usize().unwrap()
+        }
+    }
+
+    /// Child intervals of the node [l..r], in lexicographic order. Leaves have
+    /// no children.
+    pub fn children(&self, l: usize, r: usize) -> Vec<(usize, usize)> {
+        let mut cvx: Vec<(usize, usize)> = Vec::new();
+        if l >= r {
+            return cvx;
+        }
+        let mut i1 = self.first_lindex(l, r);
+        cvx.push((l, i1 - 1));
+        loop {
+            let i2 = self.next_l[i1];
+            if i2 > r {
+                break;
+            }
+            cvx.push((i1, i2 - 1));
+            i1 = i2;
+        }
+        cvx.push((i1, r));
+        cvx
+    }
+
+    /// Child of the node [l..r] whose edge label starts with the char c, if
+    /// any, walking the l-indices as children does.
+    pub fn child(&self, l: usize, r: usize, c: u8) -> Option<(usize, usize)> {
+        if l >= r {
+            return None;
+        }
+        let depth = self.string_depth(l, r);
+        // child [i1..i2-1], or [i1..r] for the last one
+        let mut i1 = l;
+        let mut i2 = self.first_lindex(l, r);
+        loop {
+            let px = self.sarray[i1].to_usize().unwrap() + depth;
+            if px < self.txt.len() && self.txt[px] == c {
+                return Some((i1, if i2 <= r { i2 - 1 } else { r }));
+            }
+            if i2 > r {
+                return None;
+            }
+            i1 = i2;
+            i2 = self.next_l[i1];
+        }
+    }
+
+    /// Label of the edge from the node parent to its child node.
+    pub fn edge_label(&self, parent: (usize, usize),
+                      child: (usize, usize)) -> &'s [u8] {
+        let sx = self.sarray[child.0].to_usize().unwrap();
+        let txt: &'s [u8] = self.txt;
+        &txt[sx + self.string_depth(parent.0, parent.1)..
+             sx + self.string_depth(child.0, child.1)]
+    }
+
+    /// Top-down search for pat. Returns the interval [l..r] of the suffixes
+    /// that have pat as a prefix, or None if pat does not occur in the text.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let esa = sufsort_rs::esa::EnhancedSA::<i32>::new(txt.as_bytes());
+    /// assert_eq!(esa.find("ISS".as_bytes()), Some((2, 3)));
+    /// assert_eq!(esa.find("PS".as_bytes()), None);
+    /// ```
+    pub fn find(&self, pat: &[u8]) -> Option<(usize, usize)> {
+        if self.txt.is_empty() {
+            return None;
+        }
+        let (mut l, mut r) = self.root();
+        let mut d: usize = 0;
+        loop {
+            let depth = self.string_depth(l, r);
+            let end = std::cmp::min(depth, pat.len());
+            if d < end {
+                let sx = self.sarray[l].to_usize().unwrap();
+                if self.txt[sx + d..sx + end] != pat[d..end] {
+                    return None;
+                }
+                d = end;
+            }
+            if d == pat.len() {
+                return Some((l, r));
+            }
+            match self.child(l, r, pat[d]) {
+                Some((cl, cr)) => { l = cl; r = cr; }
+                None => return None,
+            }
+        }
+    }
+
+    /// Suffix link of the internal node [l..r] spelling a.w, i.e., the interval
+    /// of w. Returns None for nodes of string depth 0. The interval of w is
+    /// found by a top-down search, in O(|w| sigma) time; SuffixLinks answers
+    /// in O(log n) time for repeated queries.
+    pub fn suffix_link(&self, l: usize, r: usize) -> Option<(usize, usize)> {
+        let depth = self.string_depth(l, r);
+        if depth == 0 {
+            return None;
+        }
+        let sx = self.sarray[l].to_usize().unwrap();
+        self.find(&self.txt[sx + 1..sx + depth])
+    }
+}
+
+/// Suffix links of an enhanced suffix array in O(log n) time, with the
+/// inverse suffix array and an RMQ over the LCP array (Abouelhoda, Kurtz
+/// and Ohlebusch, 2004) : the interval of w is the maximal interval around
+/// the row of the suffix following the node's first suffix whose lcp values
+/// are at least |w|.
+pub struct SuffixLinks<'e, 's, T> where T: std::cmp::Ord + std::fmt::Debug {
+    esa: &'e EnhancedSA<'s, T>,
+    pub isa: Vec<T>,
+    rmq: RMQ<'e, T, u64>,
+}
+
+impl<'e, 's, T> SuffixLinks<'e, 's, T>
+    where T: std::clone::Clone + std::marker::Copy +
+             std::ops::Add + std::ops::Sub<Output=T> +
+             std::cmp::Ord + std::fmt::Debug +
+             num::ToPrimitive + num::FromPrimitive + num::One + num::Zero {
+
+    /// Construct the suffix links of esa, of a non-empty text.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let esa = sufsort_rs::esa::EnhancedSA::<i32>::new(txt.as_bytes());
+    /// let links = sufsort_rs::esa::SuffixLinks::new(&esa);
+    /// assert_eq!(links.suffix_link(2, 3), Some((9, 10))); // ISSI -> SSI
+    /// assert_eq!(links.suffix_link(0, 10), None);
+    /// ```
+    pub fn new(esa: &'e EnhancedSA<'s, T>) -> Self {
+        assert!(!esa.is_empty());
+        SuffixLinks{esa, isa: construct_isa(&esa.sarray), rmq: RMQ::new(&esa.lcp)}
+    }
+
+    // min of lcp[a..=b]
+    fn min_lcp(&self, a: usize, b: usize) -> usize {
+        self.esa.lcp[self.rmq.query(a, b)].to_usize().unwrap()
+    }
+
+    /// Suffix link of the node [l..r], as EnhancedSA::suffix_link
+    pub fn suffix_link(&self, l: usize, r: usize) -> Option<(usize, usize)> {
+        let n = self.esa.len();
+        let depth = self.esa.string_depth(l, r);
+        if depth == 0 {
+            return None;
+        }
+        let d = depth - 1;
+        let sx = self.esa.sarray[l].to_usize().unwrap();
+        if d == 0 {
+            return Some(self.esa.root());
+        }
+        let q = self.isa[sx + 1].to_usize().unwrap();
+        // first row a with min_lcp(a + 1, q) >= d
+        let (mut a, mut b) = (0, q);
+        while a < b {
+            let mid = a + (b - a) / 2;
+            if self.min_lcp(mid + 1, q) >= d { b = mid; } else { a = mid + 1; }
+        }
+        let left = a;
+        // last row b with min_lcp(q + 1, b) >= d
+        let (mut a, mut b) = (q, n - 1);
+        while a < b {
+            let mid = b - (b - a) / 2;
+            if self.min_lcp(q + 1, mid) >= d { a = mid; } else { b = mid - 1; }
+        }
+        Some((left, a))
+    }
+}
@@ -0,0 +1,422 @@
+use std::collections::BinaryHeap;
+use std::cmp::Reverse;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use sufsort::SA;
+
+// records of the external sorts : three u64 values
+type Record = [u64; 3];
+const RECORD_BYTES: usize = 24;
+const IO_BUFFER: usize = 1 << 16;
+
+/// Suffix array construction for texts that do not fit in memory, by prefix
+/// doubling with external merge sorts. Each round sorts the (rank[i],
+/// rank[i+h], i) triples in runs of at most mem_limit bytes that are written
+/// to temporary files in tmp_dir and merged at most fan_in() at a time, so
+/// the memory used and the number of open files are bounded independently
+/// of the text length.
+///
+/// The suffix array is written as little-endian i64 values, in the same
+/// order as SA::new, and can be loaded with load_sa.
+pub struct ExternalSuffixSorter {
+    pub tmp_dir: PathBuf,
+    pub mem_limit: usize,
+}
+
+impl ExternalSuffixSorter {
+    /// Sorter using tmp_dir for temporary files and about mem_limit bytes of
+    /// memory for the sorted runs.
+    pub fn new<P: AsRef<Path>>(tmp_dir: P, mem_limit: usize) -> Self {
+        ExternalSuffixSorter{tmp_dir: tmp_dir.as_ref().to_path_buf(), mem_limit}
+    }
+
+    /// Construct the suffix array of the text in the file input and write it
+    /// to the file output. Returns the length of the text.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let dir = std::env::temp_dir().join("sufsort_rs_doc_external");
+    /// std::fs::create_dir_all(&dir).unwrap();
+    /// std::fs::write(dir.join("txt"), "MISSISSIPPI").unwrap();
+    /// let sorter = sufsort_rs::external::ExternalSuffixSorter::new(&dir, 1 << 20);
+    /// sorter.construct(dir.join("txt"), dir.join("sa")).unwrap();
+    /// let txt = std::fs::read(dir.join("txt")).unwrap();
+    /// let say = sufsort_rs::external::load_sa(&txt, dir.join("sa")).unwrap();
+    /// assert_eq!(say.sarray, &[10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2]);
+    /// std::fs::remove_dir_all(&dir).unwrap();
+    /// ```
+    pub fn construct<P: AsRef<Path>, Q: AsRef<Path>>(&self, input: P,
+                                                     output: Q) -> io::Result<usize> {
+        let work = TempDir::new(&self.tmp_dir)?;
+        let rank_path = work.path.join("rank");
+        let sa_path = work.path.join("sa");
+
+        // initial ranks are the characters, shifted so that 0 is the rank of
+        // the empty suffix past the end of the text
+        let mut n: usize = 0;
+        {
+            let reader = BufReader::with_capacity(IO_BUFFER, File::open(input)?);
+            let mut writer = BufWriter::with_capacity(IO_BUFFER, File::create(&rank_path)?);
+            for c in reader.bytes() {
+                writer.write_all(&(c? as u64 + 1).to_le_bytes())?;
+                n += 1;
+            }
+            writer.flush()?;
+        }
+
+        let mut h: usize = 1;
+        loop {
+            // sort the suffixes by their first 2h characters
+            let pairs = RankPairs::new(&rank_path, n, h)?;
+            let sorted = self.sort(pairs, |r| (r[0], r[1], r[2]), &work, "pairs")?;
+            let mut sa_writer = BufWriter::with_capacity(IO_BUFFER, File::create(&sa_path)?);
+            let mut rank: u64 = 0;
+            let mut prev: Option<(u64, u64)> = None;
+            // new ranks, sorted back in text order below
+            let ranks_path = work.path.join("ranks");
+            {
+                let mut writer = BufWriter::with_capacity(IO_BUFFER, File::create(&ranks_path)?);
+                for r in sorted {
+                    let r = r?;
+                    if prev != Some((r[0], r[1])) {
+                        rank += 1;
+                        prev = Some((r[0], r[1]));
+                    }
+                    sa_writer.write_all(&(r[2] as i64).to_le_bytes())?;
+                    write_record(&mut writer, &[r[2], rank, 0])?;
+                }
+                writer.flush()?;
+            }
+            sa_writer.flush()?;
+            drop(sa_writer);
+            if rank as usize == n || h >= n {
+                break;
+            }
+            let ranks = RecordReader::open(&ranks_path)?;
+            let by_pos = self.sort(ranks, |r| (r[0], 0, 0), &work, "ranks")?;
+            let mut writer = BufWriter::with_capacity(IO_BUFFER, File::create(&rank_path)?);
+            for r in by_pos {
+                writer.write_all(&r?[1].to_le_bytes())?;
+            }
+            writer.flush()?;
+            h *= 2;
+        }
+        // the temporary directory may be on another file system
+        if fs::rename(&sa_path, output.as_ref()).is_err() {
+            fs::copy(&sa_path, output)?;
+        }
+        Ok(n)
+    }
+
+    /// Number of runs merged at a time : one IO buffer per run, and one for
+    /// the output, fit in mem_limit bytes.
+    pub fn fan_in(&self) -> usize {
+        std::cmp::max(self.mem_limit / IO_BUFFER, 3) - 1
+    }
+
+    // sort the records by key with sorted runs of at most mem_limit bytes,
+    // merged fan_in at a time into longer runs until a single pass is left
+    fn sort<I, K>(&self, records: I, key: K, work: &TempDir,
+                  name: &str) -> io::Result<MergeReader>
+        where I: Iterator<Item=io::Result<Record>>,
+              K: Fn(&Record) -> (u64, u64, u64) + Copy + 'static {
+        let run_len = std::cmp::max(self.mem_limit / RECORD_BYTES, 1024);
+        let mut runs: Vec<PathBuf> = Vec::new();
+        let mut buf: Vec<Record> = Vec::with_capacity(run_len);
+        let mut records = records.peekable();
+        while records.peek().is_some() {
+            buf.clear();
+            while buf.len() < run_len {
+                match records.next() {
+                    Some(r) => buf.push(r?),
+                    None => break,
+                }
+            }
+            buf.sort_unstable_by_key(key);
+            let path = work.path.join(format!("{}.run{}", name, runs.len()));
+            let mut writer = BufWriter::with_capacity(IO_BUFFER, File::create(&path)?);
+            for r in buf.iter() {
+                write_record(&mut writer, r)?;
+            }
+            writer.flush()?;
+            runs.push(path);
+        }
+        let fan_in = self.fan_in();
+        let mut level = 0;
+        while runs.len() > fan_in {
+            level += 1;
+            let mut merged: Vec<PathBuf> = Vec::with_capacity(runs.len().div_ceil(fan_in));
+            for group in runs.chunks(fan_in) {
+                let path = work.path.join(format!("{}.{}.run{}", name, level, merged.len()));
+                let mut writer = BufWriter::with_capacity(IO_BUFFER, File::create(&path)?);
+                for r in MergeReader::new(group, Box::new(key))? {
+                    write_record(&mut writer, &r?)?;
+                }
+                writer.flush()?;
+                for run in group {
+                    fs::remove_file(run)?;
+                }
+                merged.push(path);
+            }
+            runs = merged;
+        }
+        MergeReader::new(&runs, Box::new(key))
+    }
+}
+
+/// Load a suffix array written by ExternalSuffixSorter::construct for the
+/// text txt, to use it with the SA APIs.
+pub fn load_sa<'s, P: AsRef<Path>>(txt: &'s [u8], path: P) -> io::Result<SA<'s, i64>> {
+    let sarray = load_i64(path)?;
+    if sarray.len() != txt.len() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                  "suffix array does not match the text length"));
+    }
+    Ok(SA{txt, sarray})
+}
+
+/// Construct the LCP array of txt from its suffix array in the file sa_path,
+/// as written by ExternalSuffixSorter::construct, and write it to the file
+/// lcp_path in the same format. Only txt and about n/q integers are kept in
+/// memory : the suffix array is streamed twice, to sample Φ and the PLCP
+/// array at every q-th text position (sparse Φ, Kärkkäinen, Manzini and
+/// Puglisi, 2009), and then to compute each LCP value starting from the
+/// closest sample. Takes O(n q) time.
+///
+/// #Example
+///
+/// ```
+/// let dir = std::env::temp_dir().join("sufsort_rs_doc_lcp");
+/// std::fs::create_dir_all(&dir).unwrap();
+/// let txt = "MISSISSIPPI".to_string();
+/// std::fs::write(dir.join("txt"), &txt).unwrap();
+/// let sorter = sufsort_rs::external::ExternalSuffixSorter::new(&dir, 1 << 20);
+/// sorter.construct(dir.join("txt"), dir.join("sa")).unwrap();
+/// sufsort_rs::external::construct_lcp_semi_external(
+///     txt.as_bytes(), dir.join("sa"), dir.join("lcp"), 4).unwrap();
+/// let lcp = sufsort_rs::external::load_i64(dir.join("lcp")).unwrap();
+/// assert_eq!(lcp, &[0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3]);
+/// std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+pub fn construct_lcp_semi_external<P: AsRef<Path>, Q: AsRef<Path>>(
+        txt: &[u8], sa_path: P, lcp_path: Q, q: usize) -> io::Result<()> {
+    assert!(q > 0);
+    let n = txt.len();
+    let sa_path = sa_path.as_ref();
+    if fs::metadata(sa_path)?.len() != 8 * n as u64 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                  "suffix array does not match the text length"));
+    }
+    // length of the common prefix of the suffixes i and j, knowing that it
+    // is at least l
+    let extend = |i: usize, j: usize, mut l: usize| -> usize {
+        while i + l < n && j + l < n && txt[i + l] == txt[j + l] {
+            l += 1;
+        }
+        l
+    };
+
+    // (1) sparse Φ : the suffix preceding each sampled suffix in SA, or n
+    let ns = n.div_ceil(q);
+    let mut splcp: Vec<usize> = vec![n; ns];
+    {
+        let mut reader = BufReader::with_capacity(IO_BUFFER, File::open(sa_path)?);
+        let mut prev = n;
+        for _ in 0..n {
+            let p = read_u64(&mut reader)? as usize;
+            if p.is_multiple_of(q) {
+                splcp[p / q] = prev;
+            }
+            prev = p;
+        }
+    }
+    // (2) sparse PLCP, using PLCP[i + q] >= PLCP[i] - q
+    let mut l: usize = 0;
+    for (k, x) in splcp.iter_mut().enumerate() {
+        l = if *x == n { 0 } else { extend(k * q, *x, l) };
+        *x = l;
+        l = l.saturating_sub(q);
+    }
+    // (3) LCP values in SA order, using PLCP[i] >= PLCP[i - i % q] - i % q
+    let mut reader = BufReader::with_capacity(IO_BUFFER, File::open(sa_path)?);
+    let mut writer = BufWriter::with_capacity(IO_BUFFER, File::create(lcp_path)?);
+    let mut prev = n;
+    for _ in 0..n {
+        let p = read_u64(&mut reader)? as usize;
+        let lx = if prev == n {
+            0
+        } else {
+            extend(p, prev, splcp[p / q].saturating_sub(p % q))
+        };
+        writer.write_all(&(lx as i64).to_le_bytes())?;
+        prev = p;
+    }
+    writer.flush()
+}
+
+/// Load a file of little-endian i64 values, such as the suffix and LCP
+/// arrays written by this module.
+pub fn load_i64<P: AsRef<Path>>(path: P) -> io::Result<Vec<i64>> {
+    let bytes = fs::read(path)?;
+    if bytes.len() % 8 != 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                  "file length is not a multiple of 8"));
+    }
+    Ok(bytes.chunks_exact(8)
+       .map(|x| i64::from_le_bytes([x[0], x[1], x[2], x[3], x[4], x[5], x[6], x[7]]))
+       .collect())
+}
+
+fn write_record<W: Write>(writer: &mut W, r: &Record) -> io::Result<()> {
+    for x in r.iter() {
+        writer.write_all(&x.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut buf: [u8; 8] = [0; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+// a uniquely named directory for the temporary files, removed on drop
+struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    fn new(parent: &Path) -> io::Result<TempDir> {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos()).unwrap_or(0);
+        for k in 0.. {
+            let path = parent.join(format!("sufsort-{}-{}-{}", std::process::id(), nanos, k));
+            match fs::create_dir(&path) {
+                Ok(()) => return Ok(TempDir{path}),
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        unreachable!()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+// (rank[i], rank[i+h], i) for all i, from two streams over the rank file
+struct RankPairs {
+    first: BufReader<File>,
+    second: BufReader<File>,
+    n: usize,
+    h: usize,
+    i: usize,
+}
+
+impl RankPairs {
+    fn new(path: &Path, n: usize, h: usize) -> io::Result<RankPairs> {
+        let first = BufReader::with_capacity(IO_BUFFER, File::open(path)?);
+        let mut second = File::open(path)?;
+        second.seek(SeekFrom::Start(8 * std::cmp::min(h, n) as u64))?;
+        Ok(RankPairs{first, second: BufReader::with_capacity(IO_BUFFER, second),
+                     n, h, i: 0})
+    }
+
+    fn read(&mut self) -> io::Result<Record> {
+        let r1 = read_u64(&mut self.first)?;
+        let r2 = if self.i + self.h < self.n { read_u64(&mut self.second)? } else { 0 };
+        Ok([r1, r2, self.i as u64])
+    }
+}
+
+impl Iterator for RankPairs {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        if self.i >= self.n {
+            return None;
+        }
+        let r = self.read();
+        self.i += 1;
+        Some(r)
+    }
+}
+
+// records stored in a file
+struct RecordReader {
+    reader: BufReader<File>,
+}
+
+impl RecordReader {
+    fn open(path: &Path) -> io::Result<RecordReader> {
+        Ok(RecordReader{reader: BufReader::with_capacity(IO_BUFFER, File::open(path)?)})
+    }
+}
+
+impl Iterator for RecordReader {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        let mut buf: [u8; RECORD_BYTES] = [0; RECORD_BYTES];
+        match self.reader.read_exact(&mut buf) {
+            Ok(()) => (),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return None,
+            Err(e) => return Some(Err(e)),
+        }
+        let mut r: Record = [0; 3];
+        for (k, x) in r.iter_mut().enumerate() {
+            let mut b: [u8; 8] = [0; 8];
+            b.copy_from_slice(&buf[8 * k..8 * k + 8]);
+            *x = u64::from_le_bytes(b);
+        }
+        Some(Ok(r))
+    }
+}
+
+type SortKey = Box<dyn Fn(&Record) -> (u64, u64, u64)>;
+// head of a run : its key, the run index and the record
+type RunHead = ((u64, u64, u64), usize, Record);
+
+// k-way merge of sorted runs
+struct MergeReader {
+    runs: Vec<RecordReader>,
+    heap: BinaryHeap<Reverse<RunHead>>,
+    key: SortKey,
+}
+
+impl MergeReader {
+    fn new(paths: &[PathBuf], key: SortKey) -> io::Result<MergeReader> {
+        let mut mx = MergeReader{runs: Vec::new(), heap: BinaryHeap::new(), key};
+        for (k, path) in paths.iter().enumerate() {
+            mx.runs.push(RecordReader::open(path)?);
+            mx.refill(k)?;
+        }
+        Ok(mx)
+    }
+
+    fn refill(&mut self, k: usize) -> io::Result<()> {
+        if let Some(r) = self.runs[k].next() {
+            let r = r?;
+            self.heap.push(Reverse(((self.key)(&r), k, r)));
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for MergeReader {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        let Reverse((_, k, r)) = self.heap.pop()?;
+        match self.refill(k) {
+            Ok(()) => Some(Ok(r)),
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
@@ -0,0 +1,164 @@
+// occurrence counts are sampled every OCC_RATE rows of the BWT
+const OCC_RATE: usize = 64;
+const NO_CODE: usize = 256;
+
+/// FM-index over the suffix array of a text, for backward search.
+///
+/// The index is built for txt followed by a virtual terminator that is
+/// smaller than all the characters, so that no sentinel byte needs to be
+/// reserved in txt. It has n+1 rows : row 0 is the suffix consisting of only
+/// the terminator and row i+1 corresponds to SA[i]. bwt[primary] is the
+/// terminator; the byte stored there is a placeholder and is never counted.
+pub struct FMIndex<'s, T> {
+    pub sarray: &'s [T],
+    pub bwt: Vec<u8>,
+    pub primary: usize,
+    // counts[c] : number of rows starting with a character smaller than c
+    counts: [usize; 257],
+    // code[c] : rank of c among the characters occurring in the text
+    code: [usize; 256],
+    sigma: usize,
+    occ: Vec<usize>,
+}
+
+impl<'s, T> FMIndex<'s, T>
+    where T: std::marker::Copy + num::ToPrimitive {
+
+    /// Construct the FM-index of txt from its suffix array sa.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let say = sufsort_rs::sufsort::SA::<i32>::new(txt.as_bytes());
+    /// let fmx = sufsort_rs::fmindex::FMIndex::new(txt.as_bytes(), &say.sarray);
+    /// assert_eq!(fmx.backward_search("ISS".as_bytes()), (2, 2));
+    /// ```
+    pub fn new(txt: &[u8], sa: &'s [T]) -> Self {
+        let n = txt.len();
+        assert!(n == sa.len());
+        let mut bwt: Vec<u8> = vec![0; n + 1];
+        let mut primary: usize = 0;
+        if n > 0 {
+            bwt[0] = txt[n - 1];
+        }
+        for (i, x) in sa.iter().enumerate() {
+            let sx = x.to_usize().unwrap();
+            if sx == 0 {
+                primary = i + 1;
+            } else {
+                bwt[i + 1] = txt[sx - 1];
+            }
+        }
+
+        let mut freq: [usize; 256] = [0; 256];
+        for c in txt.iter() {
+            freq[*c as usize] += 1;
+        }
+        let mut counts: [usize; 257] = [0; 257];
+        let mut code: [usize; 256] = [NO_CODE; 256];
+        let mut sigma: usize = 0;
+        counts[0] = 1;
+        for c in 0..256 {
+            counts[c + 1] = counts[c] + freq[c];
+            if freq[c] > 0 {
+                code[c] = sigma;
+                sigma += 1;
+            }
+        }
+
+        let nblocks = (n + 1) / OCC_RATE + 1;
+        let mut occ: Vec<usize> = vec![0; nblocks * sigma];
+        let mut cur: Vec<usize> = vec![0; sigma];
+        for (i, c) in bwt.iter().enumerate() {
+            if i.is_multiple_of(OCC_RATE) {
+                let b = i / OCC_RATE;
+                occ[b * sigma..(b + 1) * sigma].copy_from_slice(&cur);
+            }
+            if i != primary {
+                cur[code[*c as usize]] += 1;
+            }
+        }
+        if (n + 1).is_multiple_of(OCC_RATE) {
+            let b = (n + 1) / OCC_RATE;
+            occ[b * sigma..(b + 1) * sigma].copy_from_slice(&cur);
+        }
+
+        FMIndex{sarray: sa, bwt, primary, counts, code, sigma, occ}
+    }
+
+    /// Length of the indexed text
+    pub fn len(&self) -> usize {
+        self.sarray.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sarray.is_empty()
+    }
+
+    /// Number of rows of the index, i.e., n + 1
+    pub fn rows(&self) -> usize {
+        self.bwt.len()
+    }
+
+    /// Number of rows starting with a character smaller than c
+    pub fn count_less(&self, c: u8) -> usize {
+        self.counts[c as usize]
+    }
+
+    /// Characters occurring in the text, in increasing order
+    pub fn alphabet(&self) -> Vec<u8> {
+        (0..256).filter(|c| self.code[*c] != NO_CODE).map(|c| c as u8).collect()
+    }
+
+    /// Number of occurrences of c in bwt[0..i]
+    pub fn rank(&self, c: u8, i: usize) -> usize {
+        let cx = self.code[c as usize];
+        if cx == NO_CODE {
+            return 0;
+        }
+        let b = i / OCC_RATE;
+        let mut rx = self.occ[b * self.sigma + cx];
+        for j in (b * OCC_RATE)..i {
+            if self.bwt[j] == c && j != self.primary {
+                rx += 1;
+            }
+        }
+        rx
+    }
+
+    /// Backward search step : given the rows [sp, ep) prefixed by a string w,
+    /// returns the rows prefixed by c.w. The result is empty if sp == ep.
+    pub fn extend_left(&self, c: u8, sp: usize, ep: usize) -> (usize, usize) {
+        let cx = self.counts[c as usize];
+        (cx + self.rank(c, sp), cx + self.rank(c, ep))
+    }
+
+    /// Text position of the suffix at the given row
+    pub fn locate(&self, row: usize) -> usize {
+        if row == 0 {
+            self.sarray.len()
+        } else {
+            self.sarray[row - 1].to_usize().unwrap()
+        }
+    }
+
+    /// Count the occurrences of pat by backward search. Returns the (left,
+    /// count) range of the suffix array, as SA::search_sa.
+    pub fn backward_search(&self, pat: &[u8]) -> (usize, usize) {
+        let (mut sp, mut ep) = (0, self.rows());
+        for c in pat.iter().rev() {
+            let (nsp, nep) = self.extend_left(*c, sp, ep);
+            sp = nsp;
+            ep = nep;
+            if sp >= ep {
+                return (sp.saturating_sub(1), 0);
+            }
+        }
+        if pat.is_empty() {
+            (0, self.len())
+        } else {
+            (sp - 1, ep - sp)
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use sufsort::construct_isa;
+use rmq::RMQ;
+
+/// Longest common extension queries on a text : the length of the longest
+/// common prefix of the suffixes starting at any two positions, answered in
+/// constant time by a range minimum query over the LCP array between their
+/// ranks.
+pub struct LCE<'s, T> where T: std::cmp::Ord + std::fmt::Debug {
+    pub isa: Vec<T>,
+    rmq: RMQ<'s, T, u64>,
+}
+
+impl<'s, T> LCE<'s, T>
+    where T: std::clone::Clone + std::marker::Copy + std::ops::Add +
+             std::cmp::Ord + std::fmt::Debug +
+             num::ToPrimitive + num::FromPrimitive + num::One + num::Zero {
+
+    /// Construct the LCE structure from the suffix array sa of a non-empty
+    /// text and its LCP array lcp.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let say = sufsort_rs::sufsort::SA::<i64>::new(txt.as_bytes());
+    /// let lcp = sufsort_rs::lcp::construct_lcp_phi(txt.as_bytes(), &say.sarray);
+    /// let lce = sufsort_rs::lce::LCE::new(&say.sarray, &lcp);
+    /// assert_eq!(lce.lce(1, 4), 4);
+    /// assert_eq!(lce.lce(2, 5), 3);
+    /// ```
+    pub fn new(sa: &[T], lcp: &'s [T]) -> Self {
+        assert!(!sa.is_empty() && sa.len() == lcp.len());
+        LCE{isa: construct_isa(sa), rmq: RMQ::new(lcp)}
+    }
+
+    /// Length of the longest common prefix of the suffixes i and j
+    pub fn lce(&self, i: usize, j: usize) -> usize {
+        if i == j {
+            return self.isa.len() - i;
+        }
+        let a = self.isa[i].to_usize().unwrap();
+        let b = self.isa[j].to_usize().unwrap();
+        let (lo, hi) = if a < b { (a, b) } else { (b, a) };
+        self.rmq.src[self.rmq.query(lo + 1, hi)].to_usize().unwrap()
+    }
+}
@@ -1,3 +1,6 @@
+use bits::BitVector;
+use sufsort::{SuffixArray, SuffixArrayMut};
+use packed::PackedSA;
 
 pub fn construct_lcp_kasai<T>(text: &[u8], sa: &Vec<T>,
                                 isa: &Vec<T>) -> Vec<T>
@@ -45,46 +48,130 @@ pub fn construct_lcp_kasai<T>(text: &[u8], sa: &Vec<T>,
     lcp
 }
 
-pub fn construct_lcp_phi<T>(text: &[u8], sa: &Vec<T>) -> Vec<T>
+pub fn construct_lcp_phi<T>(text: &[u8], sa: &[T]) -> Vec<T>
     where T: std::clone::Clone + std::marker::Copy +
-                std::ops::Add + std::ops::Sub<Output=T> +
-                std::cmp::Ord +
-                num::ToPrimitive + num::One + num::Zero {
+                num::ToPrimitive + num::FromPrimitive + num::Zero {
+    let plcp = construct_plcp(text, sa);
+    sa.iter().map(|x| plcp[x.to_usize().unwrap()]).collect()
+}
+
+/// Construct the permuted LCP array of text with the Φ algorithm : PLCP[i]
+/// is the LCP value of the suffix i with the suffix preceding it in sa, i.e.,
+/// LCP[i] = PLCP[SA[i]].
+///
+/// #Example
+///
+/// ```
+/// let txt = "MISSISSIPPI".to_string();
+/// let say = sufsort_rs::sufsort::SA::<i32>::new(txt.as_bytes());
+/// let plcp = sufsort_rs::lcp::construct_plcp(txt.as_bytes(), &say.sarray);
+/// assert_eq!(plcp, &[0, 4, 3, 2, 1, 1, 0, 1, 1, 0, 0]);
+/// ```
+pub fn construct_plcp<T>(text: &[u8], sa: &[T]) -> Vec<T>
+    where T: std::clone::Clone + std::marker::Copy +
+                num::ToPrimitive + num::FromPrimitive + num::Zero {
+    let mut plcp : Vec<T> = vec![T::zero(); sa.len()];
+    construct_plcp_into(text, sa, &mut plcp);
+    plcp
+}
+
+/// Write the permuted LCP array of text to plcp, given the suffix array sa,
+/// for any storage of both. Φ is computed in plcp and overwritten by the
+/// PLCP values, so no other array is used.
+///
+/// #Example
+///
+/// ```
+/// use sufsort_rs::sufsort::SuffixArray;
+/// let txt = "MISSISSIPPI".to_string();
+/// let sa40 = sufsort_rs::packed::SA40::new(txt.as_bytes());
+/// let mut plcp = sufsort_rs::packed::PackedSA::zeroed(sa40.len());
+/// sufsort_rs::lcp::construct_plcp_into(txt.as_bytes(), &sa40, &mut plcp);
+/// assert_eq!(plcp.iter().collect::<Vec<usize>>(), &[0, 4, 3, 2, 1, 1, 0, 1, 1, 0, 0]);
+/// ```
+pub fn construct_plcp_into<S, D>(text: &[u8], sa: &S, plcp: &mut D)
+    where S: SuffixArray + ?Sized, D: SuffixArrayMut + ?Sized {
     let n = sa.len();
-    assert!(n == text.len());
-    let mut lcp : Vec<T> = vec![T::zero(); n];
-    let mut plcp : Vec<T> = vec![T::zero(); n];
-    let mut sai_1 : T = T::zero();
-    // (1) Calculate PHI
+    assert!(n == text.len() && n == plcp.len());
+    // (1) Calculate PHI, with n for SA[0], which has no previous suffix
     for i in 0..n {
-        let sai = sa[i].to_usize().unwrap();
-        plcp[sai] = sai_1;
-        sai_1 = sa[i].clone();
+        plcp.set(sa.at(i), if i == 0 { n } else { sa.at(i - 1) });
     }
 
     // (2) Calculate Permuted LCP array.
-    let mut max_size : usize = 0;
-    let mut l : T = T::zero();
-    for i in 0..n-1 {
-        let phii =  plcp[i].to_usize().unwrap();
-        let mut ldx =  l.to_usize().unwrap();
-
-        while std::cmp::max(i+ldx, phii+ldx) < text.len() &&
-                text[i+ldx] == text[phii+ldx] {
-            l = l + T::one();
-            ldx =  l.to_usize().unwrap();
+    let mut l: usize = 0;
+    for i in 0..n {
+        let phii = plcp.at(i);
+        if phii == n {
+            plcp.set(i, 0);
+            l = 0;
+            continue;
         }
-        plcp[i] = T::zero() + l;
-        if l != T::zero() {
-            max_size = std::cmp::max(max_size, l.to_usize().unwrap());
-            l = l - T::one();
+        while std::cmp::max(i + l, phii + l) < n && text[i + l] == text[phii + l] {
+            l += 1;
         }
+        plcp.set(i, l);
+        l = l.saturating_sub(1);
     }
-    for i in 0..n {
-        let sai = sa[i].to_usize().unwrap();
-        lcp[i] = plcp[sai];
+}
+
+/// Permuted LCP array in 2n bits (Sadakane, 2007). Since PLCP[i] + i is
+/// non-decreasing, the bits at PLCP[i] + 2i are set in a bit vector of
+/// length 2n, and PLCP[i] = select1(i) - 2i.
+pub struct SuccinctPLCP {
+    pub bits: BitVector,
+}
+
+impl SuccinctPLCP {
+    /// Encode the permuted LCP array plcp.
+    pub fn new<T>(plcp: &[T]) -> Self
+        where T: num::ToPrimitive {
+        let n = plcp.len();
+        let ones = plcp.iter().enumerate()
+            .map(|(i, x)| x.to_usize().unwrap() + 2 * i);
+        SuccinctPLCP{bits: BitVector::from_positions(2 * n + 1, ones)}
+    }
+
+    /// Construct the succinct PLCP of text from its suffix array sa.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let say = sufsort_rs::sufsort::SA::<i32>::new(txt.as_bytes());
+    /// let slcp = sufsort_rs::lcp::SuccinctPLCP::from_sa(txt.as_bytes(), &say.sarray);
+    /// assert_eq!(slcp.plcp(1), 4);
+    /// assert_eq!(slcp.lcp(&say.sarray, 3), 4);
+    /// ```
+    pub fn from_sa<S: SuffixArray + ?Sized>(text: &[u8], sa: &S) -> Self {
+        let mut plcp: Vec<usize> = vec![0; sa.len()];
+        construct_plcp_into(text, sa, &mut plcp);
+        SuccinctPLCP::new(&plcp)
+    }
+
+    /// Number of values
+    pub fn len(&self) -> usize {
+        self.bits.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// PLCP[i]
+    pub fn plcp(&self, i: usize) -> usize {
+        self.bits.select1(i) - 2 * i
+    }
+
+    /// LCP[i], from the suffix array sa
+    pub fn lcp<S: SuffixArray + ?Sized>(&self, sa: &S, i: usize) -> usize {
+        self.plcp(sa.at(i))
+    }
+
+    /// Size of the encoding in bytes, including the select samples
+    pub fn size_in_bytes(&self) -> usize {
+        self.bits.size_in_bytes()
     }
-    lcp
 }
 
 pub fn construct_lcp_from_sa<T>(text: &[u8], sa: &Vec<T>, isa: &Vec<T>) -> Vec<T>
@@ -140,3 +227,211 @@ pub fn construct_lcp_from_sa<T>(text: &[u8], sa: &Vec<T>, isa: &Vec<T>) -> Vec<T
     lcp
 }
 
+
+/// Iterator over the lcp-intervals of an LCP array, generated bottom-up by
+/// the stack based traversal of Abouelhoda et al. Each interval is reported
+/// as (lcp, left, right), with left and right inclusive, after all the
+/// intervals nested inside it.
+pub struct LcpIntervals<'s, T> {
+    lcp: &'s [T],
+    // (lcp value, left boundary) of the intervals that are still open
+    stack: Vec<(usize, usize)>,
+    // next position of the lcp array to process
+    idx: usize,
+    // left boundary of the last closed interval or idx - 1
+    left: usize,
+    // whether the bottom 0-interval has an lcp-index, i.e., it is a proper
+    // interval and not just an artifact of the traversal
+    zero_seen: bool,
+}
+
+impl<'s, T> Iterator for LcpIntervals<'s, T>
+    where T: num::ToPrimitive {
+    type Item = (usize, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.lcp.len();
+        while self.idx <= n {
+            let cur = if self.idx < n {
+                Some(self.lcp[self.idx].to_usize().unwrap())
+            } else {
+                None
+            };
+            // close the intervals ending at idx - 1, one per call
+            if let Some(&(lv, lb)) = self.stack.last() {
+                if cur.is_none_or(|x| x < lv) {
+                    self.stack.pop();
+                    self.left = lb;
+                    if self.stack.is_empty() && !self.zero_seen {
+                        continue;
+                    }
+                    return Some((lv, lb, self.idx - 1));
+                }
+            }
+            if let Some(x) = cur {
+                if x == 0 {
+                    self.zero_seen = true;
+                }
+                if x > self.stack.last().unwrap().0 {
+                    self.stack.push((x, self.left));
+                }
+            }
+            self.idx += 1;
+            self.left = self.idx - 1;
+        }
+        None
+    }
+}
+
+/// Enumerate all the lcp-intervals (lcp, left, right) of the LCP array lcp
+/// bottom-up. lcp is expected to be as constructed by the construct_lcp_*
+/// functions, i.e., lcp[0] = 0 and lcp[i] is the length of the longest common
+/// prefix of the suffixes SA[i-1] and SA[i]. The root interval (0, 0, n-1) is
+/// reported last, if the suffixes do not all share a common prefix.
+///
+/// #Example
+/// ```
+/// let lcp: Vec<i32> = vec![0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3];
+/// let ivx: Vec<(usize, usize, usize)> =
+///     sufsort_rs::lcp::lcp_intervals(&lcp).collect();
+/// assert_eq!(ivx, &[(4, 2, 3), (1, 0, 3), (1, 5, 6), (2, 7, 8),
+///                   (3, 9, 10), (1, 7, 10), (0, 0, 10)]);
+/// ```
+pub fn lcp_intervals<T>(lcp: &[T]) -> LcpIntervals<'_, T>
+    where T: num::ToPrimitive {
+    LcpIntervals{
+        lcp,
+        stack: vec![(0, 0)],
+        idx: 1,
+        left: 0,
+        zero_seen: false,
+    }
+}
+
+// values of a CompactLCP from OVERFLOW on are kept in its exception table
+const OVERFLOW: u8 = u8::MAX;
+
+/// LCP array storing the values below 255 in one byte each, and the larger
+/// ones in a table of exceptions sorted by position.
+pub struct CompactLCP {
+    small: Vec<u8>,
+    exceptions: Vec<(usize, usize)>,
+}
+
+impl CompactLCP {
+    /// Compact copy of the LCP array lcp.
+    pub fn new<T>(lcp: &[T]) -> Self
+        where T: num::ToPrimitive {
+        lcp.iter().map(|x| x.to_usize().unwrap()).collect()
+    }
+
+    /// Construct the compact LCP array of text from its suffix array sa,
+    /// in any storage, with the Φ algorithm. The PLCP array is kept packed
+    /// in ceil(log2(n+1)) bits per value, and the LCP values are streamed
+    /// from it in SA order, so no full width array is used.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let say = sufsort_rs::sufsort::SA::<i32>::new(txt.as_bytes());
+    /// let clcp = sufsort_rs::lcp::CompactLCP::from_sa(txt.as_bytes(), &say.sarray);
+    /// assert_eq!(clcp.get(3), 4);
+    /// let lcp: Vec<usize> = clcp.iter().collect();
+    /// assert_eq!(lcp, &[0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3]);
+    /// ```
+    pub fn from_sa<S: SuffixArray + ?Sized>(text: &[u8], sa: &S) -> Self {
+        let mut plcp = PackedSA::zeroed(sa.len());
+        construct_plcp_into(text, sa, &mut plcp);
+        (0..sa.len()).map(|i| plcp.at(sa.at(i))).collect()
+    }
+
+    /// Number of values
+    pub fn len(&self) -> usize {
+        self.small.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.small.is_empty()
+    }
+
+    /// LCP[i]
+    pub fn get(&self, i: usize) -> usize {
+        let x = self.small[i];
+        if x < OVERFLOW {
+            x as usize
+        } else {
+            let k = self.exceptions.binary_search_by_key(&i, |e| e.0).unwrap();
+            self.exceptions[k].1
+        }
+    }
+
+    /// Iterator over the values in order
+    pub fn iter(&self) -> CompactLCPIter<'_> {
+        CompactLCPIter{lcp: self, i: 0, k: 0}
+    }
+
+    /// Number of values kept in the exception table
+    pub fn exceptions(&self) -> usize {
+        self.exceptions.len()
+    }
+
+    /// Size of the values and exceptions in bytes
+    pub fn size_in_bytes(&self) -> usize {
+        self.small.len() +
+            self.exceptions.len() * std::mem::size_of::<(usize, usize)>()
+    }
+}
+
+impl std::iter::FromIterator<usize> for CompactLCP {
+    fn from_iter<I: IntoIterator<Item=usize>>(iter: I) -> Self {
+        let mut exceptions: Vec<(usize, usize)> = Vec::new();
+        let small: Vec<u8> = iter.into_iter().enumerate().map(|(i, lx)| {
+            if lx < OVERFLOW as usize {
+                lx as u8
+            } else {
+                exceptions.push((i, lx));
+                OVERFLOW
+            }
+        }).collect();
+        CompactLCP{small, exceptions}
+    }
+}
+
+/// Iterator over the values of a CompactLCP, following the exception table
+/// alongside the values instead of searching it.
+pub struct CompactLCPIter<'s> {
+    lcp: &'s CompactLCP,
+    i: usize,
+    // next exception
+    k: usize,
+}
+
+impl<'s> Iterator for CompactLCPIter<'s> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        let x = *self.lcp.small.get(self.i)?;
+        self.i += 1;
+        if x < OVERFLOW {
+            Some(x as usize)
+        } else {
+            self.k += 1;
+            Some(self.lcp.exceptions[self.k - 1].1)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let r = self.lcp.len() - self.i;
+        (r, Some(r))
+    }
+}
+
+impl<'s> IntoIterator for &'s CompactLCP {
+    type Item = usize;
+    type IntoIter = CompactLCPIter<'s>;
+
+    fn into_iter(self) -> CompactLCPIter<'s> {
+        self.iter()
+    }
+}
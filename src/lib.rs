@@ -1,7 +1,32 @@
 extern crate libc;
 extern crate num;
+extern crate flate2;
+extern crate regex_automata;
 
 pub mod sufsort;
 pub mod lcp;
 pub mod rmq;
+pub mod nsv;
+pub mod esa;
+pub mod repeats;
+pub mod fmindex;
+pub mod matching;
+pub mod lz77;
+pub mod lce;
+pub mod runs;
+pub mod lyndon;
+pub mod ebwt;
+pub mod sais;
+pub mod msbwt;
+pub mod compress;
+pub mod external;
+pub mod sparse;
+pub mod bits;
+pub mod packed;
+pub mod dna;
+pub mod seqio;
+pub mod approx;
+pub mod bifm;
+pub mod wildcard;
+pub mod regex;
 
@@ -0,0 +1,57 @@
+use nsv::construct_nsv;
+
+/// Lyndon factorization of text by Duval's algorithm : the unique
+/// factorization into a non-increasing sequence of Lyndon words, given as
+/// (start, length) pairs. Runs in linear time and constant extra space.
+///
+/// #Example
+///
+/// ```
+/// let fx = sufsort_rs::lyndon::lyndon_factorization("banana".as_bytes());
+/// assert_eq!(fx, &[(0, 1), (1, 2), (3, 2), (5, 1)]); // b, an, an, a
+/// ```
+pub fn lyndon_factorization(text: &[u8]) -> Vec<(usize, usize)> {
+    let n = text.len();
+    let mut fx: Vec<(usize, usize)> = Vec::new();
+    let mut i: usize = 0;
+    while i < n {
+        // text[i..j] is a power of a Lyndon word of length j - k, followed
+        // by a prefix of it
+        let mut j = i + 1;
+        let mut k = i;
+        while j < n && text[k] <= text[j] {
+            if text[k] < text[j] {
+                k = i;
+            } else {
+                k += 1;
+            }
+            j += 1;
+        }
+        while i <= k {
+            fx.push((i, j - k));
+            i += j - k;
+        }
+    }
+    fx
+}
+
+/// Lyndon array of a text from its inverse suffix array isa : for each
+/// position i, the length of the longest Lyndon word starting at i. That word
+/// ends right before the next suffix that is lexicographically smaller than
+/// the suffix i, i.e., the next smaller value of isa.
+///
+/// #Example
+///
+/// ```
+/// let txt = "banana".to_string();
+/// let say = sufsort_rs::sufsort::SA::<i32>::new(txt.as_bytes());
+/// let isa = sufsort_rs::sufsort::construct_isa(&say.sarray);
+/// let lyn = sufsort_rs::lyndon::lyndon_array(&isa);
+/// assert_eq!(lyn, &[1, 2, 1, 2, 1, 1]);
+/// ```
+pub fn lyndon_array<T>(isa: &[T]) -> Vec<usize>
+    where T: std::cmp::Ord {
+    construct_nsv(isa).into_iter().enumerate()
+        .map(|(i, j)| j - i)
+        .collect()
+}
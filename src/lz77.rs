@@ -0,0 +1,178 @@
+use sufsort::{SA, construct_isa};
+use lcp::construct_lcp_phi;
+use nsv::{construct_psv, construct_nsv};
+use rmq::RMQ;
+
+/// A phrase of the LZ77 factorization : either a single character that does
+/// not occur before, or a copy of length chars starting at an earlier text
+/// position source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phrase {
+    Literal(u8),
+    Copy{source: usize, length: usize},
+}
+
+impl Phrase {
+    /// Number of text characters covered by the phrase
+    pub fn length(&self) -> usize {
+        match *self {
+            Phrase::Literal(_) => 1,
+            Phrase::Copy{length, ..} => length,
+        }
+    }
+}
+
+/// Greedy LZ77 factorizations of a text, where the source of a copy phrase
+/// may (overlapping) or may not (non_overlapping) overlap the phrase itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LZ77 {
+    pub non_overlapping: Vec<Phrase>,
+    pub overlapping: Vec<Phrase>,
+}
+
+/// Compute both the greedy non-overlapping and overlapping LZ77
+/// factorizations of text. The overlapping factorization takes linear time
+/// after the suffix array, but the non-overlapping one takes O(n log n) time
+/// in the worst case, which bounds the whole; see lz77_non_overlapping.
+///
+/// #Example
+///
+/// ```
+/// use sufsort_rs::lz77::Phrase;
+/// let lz = sufsort_rs::lz77::lz77_factorize("abababb".as_bytes());
+/// assert_eq!(lz.overlapping,
+///            &[Phrase::Literal(b'a'), Phrase::Literal(b'b'),
+///              Phrase::Copy{source: 0, length: 4},
+///              Phrase::Copy{source: 1, length: 1}]);
+/// assert_eq!(lz.non_overlapping,
+///            &[Phrase::Literal(b'a'), Phrase::Literal(b'b'),
+///              Phrase::Copy{source: 0, length: 2},
+///              Phrase::Copy{source: 0, length: 2},
+///              Phrase::Copy{source: 1, length: 1}]);
+/// ```
+pub fn lz77_factorize(text: &[u8]) -> LZ77 {
+    let say = SA::<i64>::new(text);
+    LZ77{
+        non_overlapping: lz77_non_overlapping(text, &say.sarray),
+        overlapping: lz77_overlapping(text, &say.sarray),
+    }
+}
+
+fn phrase(text: &[u8], i: usize, source: usize, length: usize) -> Phrase {
+    if length == 0 {
+        Phrase::Literal(text[i])
+    } else {
+        Phrase::Copy{source, length}
+    }
+}
+
+/// Greedy LZ77 factorization of text where sources may overlap their phrase,
+/// from its suffix array sa. The longest previous factor at a position is
+/// shared with one of the closest suffixes in sa that start before it, which
+/// are the previous and next smaller values of sa (Kärkkäinen, Kempa and
+/// Puglisi, 2013). Runs in linear time.
+pub fn lz77_overlapping<T>(text: &[u8], sa: &[T]) -> Vec<Phrase>
+    where T: std::clone::Clone + std::marker::Copy + std::ops::Add +
+             std::cmp::Ord +
+             num::ToPrimitive + num::FromPrimitive + num::One + num::Zero {
+    let n = text.len();
+    assert!(n == sa.len());
+    let isa = construct_isa(sa);
+    let psv = construct_psv(sa);
+    let nsv = construct_nsv(sa);
+    let mut phrases: Vec<Phrase> = Vec::new();
+    let mut i: usize = 0;
+    while i < n {
+        let r = isa[i].to_usize().unwrap();
+        let (mut source, mut length) = (n, 0);
+        for c in [psv[r], nsv[r]].iter().filter(|&&c| c != n) {
+            let j = sa[*c].to_usize().unwrap();
+            // both candidates share at most the phrase length with i,
+            // which bounds the total work by n
+            let mut l = 0;
+            while i + l < n && text[j + l] == text[i + l] {
+                l += 1;
+            }
+            if l > length {
+                source = j;
+                length = l;
+            }
+        }
+        phrases.push(phrase(text, i, source, length));
+        i += std::cmp::max(length, 1);
+    }
+    phrases
+}
+
+/// Greedy LZ77 factorization of text where sources do not overlap their
+/// phrase, from its suffix array sa. Among the sources of maximal length,
+/// the leftmost one is chosen.
+///
+/// The phrase at i is extended one character at a time: the occurrences of
+/// text[i..i+l] form an interval of rows around the row of i, found by
+/// binary search with an RMQ over the LCP array, and the leftmost one is
+/// answered by an RMQ over sa. The extension to l succeeds iff that source
+/// ends by i. Each step takes O(log n) time and the phrase lengths sum to
+/// n, hence O(n log n) time in the worst case, and O(n) words of space.
+///
+/// This is not linear : the closest earlier suffixes in sa, which give the
+/// overlapping factorization, may all overlap the phrase, and walking their
+/// PSV/NSV chains has no linear bound (it is quadratic on a^n). Linear time
+/// would need the longest previous non-overlapping factor array of
+/// Crochemore and Tischler, 2011, which is not implemented.
+pub fn lz77_non_overlapping<T>(text: &[u8], sa: &[T]) -> Vec<Phrase>
+    where T: std::clone::Clone + std::marker::Copy +
+             std::cmp::Ord + std::fmt::Debug +
+             num::ToPrimitive + num::FromPrimitive + num::One + num::Zero {
+    let n = text.len();
+    assert!(n == sa.len());
+    if n <= 1 {
+        return text.iter().map(|c| Phrase::Literal(*c)).collect();
+    }
+    let isa = construct_isa(sa);
+    let lcp = construct_lcp_phi(text, sa);
+    let lcp_rmq: RMQ<T, u64> = RMQ::new(&lcp);
+    let sa_rmq: RMQ<T, u64> = RMQ::new(sa);
+    // min of lcp[a..=b], the lcp of the suffixes at rows a - 1 and b
+    let min_lcp = |a: usize, b: usize| -> usize {
+        lcp[lcp_rmq.query(a, b)].to_usize().unwrap()
+    };
+
+    let mut phrases: Vec<Phrase> = Vec::new();
+    let mut i: usize = 0;
+    while i < n {
+        let r = isa[i].to_usize().unwrap();
+        // rows [lo, hi] of the occurrences of text[i..i+length], which only
+        // shrink as length grows
+        let (mut lo, mut hi) = (0, n - 1);
+        let (mut source, mut length) = (n, 0);
+        while i + length < n {
+            let l = length + 1;
+            // first row a in [lo, r] with min_lcp(a + 1, r) >= l
+            let (mut a, mut b) = (lo, r);
+            while a < b {
+                let mid = a + (b - a) / 2;
+                if min_lcp(mid + 1, r) >= l { b = mid; } else { a = mid + 1; }
+            }
+            let nlo = a;
+            // last row b in [r, hi] with min_lcp(r + 1, b) >= l
+            let (mut a, mut b) = (r, hi);
+            while a < b {
+                let mid = b - (b - a) / 2;
+                if min_lcp(r + 1, mid) >= l { a = mid; } else { b = mid - 1; }
+            }
+            let nhi = a;
+            let j = sa[sa_rmq.query(nlo, nhi)].to_usize().unwrap();
+            if j + l > i {
+                break;
+            }
+            lo = nlo;
+            hi = nhi;
+            source = j;
+            length = l;
+        }
+        phrases.push(phrase(text, i, source, length));
+        i += std::cmp::max(length, 1);
+    }
+    phrases
+}
@@ -0,0 +1,265 @@
+use sufsort::SA;
+use lcp::construct_lcp_phi;
+use nsv::{construct_psv, construct_nsv};
+use fmindex::FMIndex;
+use dna;
+
+/// Index of a text for computing the matching statistics of queries against
+/// it. Backward search on the FM-index extends matches to the left, and the
+/// LCP array with its PSV/NSV arrays shortens them to the parent interval
+/// when they can not be extended, in O(1) steps.
+pub struct MatchingIndex<'s, T> {
+    pub fm: FMIndex<'s, T>,
+    pub lcp: Vec<T>,
+    psv: Vec<usize>,
+    nsv: Vec<usize>,
+}
+
+impl<'s, T> MatchingIndex<'s, T>
+    where T: std::clone::Clone + std::marker::Copy +
+             std::ops::Add + std::ops::Sub<Output=T> +
+             std::cmp::Ord +
+             num::ToPrimitive + num::FromPrimitive + num::One + num::Zero {
+
+    /// Construct the matching statistics index of txt from its suffix array.
+    pub fn new(txt: &[u8], sa: &'s [T]) -> Self {
+        let lcp = construct_lcp_phi(txt, sa);
+        let psv = construct_psv(&lcp);
+        let nsv = construct_nsv(&lcp);
+        MatchingIndex{fm: FMIndex::new(txt, sa), lcp, psv, nsv}
+    }
+
+    // lcp of the rows r-1 and r of the FM-index, with 0 beyond the last row
+    fn row_lcp(&self, r: usize) -> usize {
+        if r >= 1 && r <= self.lcp.len() {
+            self.lcp[r - 1].to_usize().unwrap()
+        } else {
+            0
+        }
+    }
+
+    /// Parent interval of the rows [sp, ep) of a non-empty string, given as
+    /// the rows [sp', ep') and its string depth.
+    pub fn parent(&self, sp: usize, ep: usize) -> (usize, usize, usize) {
+        let (a, b) = (self.row_lcp(sp), self.row_lcp(ep));
+        let lv = std::cmp::max(a, b);
+        if lv == 0 {
+            return (0, self.fm.rows(), 0);
+        }
+        // the lv-interval around row k, from the previous and next smaller
+        // lcp values.
+        let k = if a >= b { sp } else { ep };
+        (self.psv[k - 1] + 1, self.nsv[k - 1] + 1, lv)
+    }
+
+    /// Walk the query right to left, calling visit(i, sp, ep, len) for each
+    /// position i with the rows [sp, ep) of the longest prefix of query[i..]
+    /// occurring in the text, and its length len.
+    pub fn walk<F>(&self, query: &[u8], mut visit: F)
+        where F: FnMut(usize, usize, usize, usize) {
+        let (mut sp, mut ep, mut len) = (0, self.fm.rows(), 0);
+        for i in (0..query.len()).rev() {
+            loop {
+                let (nsp, nep) = self.fm.extend_left(query[i], sp, ep);
+                if nsp < nep {
+                    sp = nsp;
+                    ep = nep;
+                    len += 1;
+                    break;
+                }
+                if len == 0 {
+                    break;
+                }
+                let (psp, pep, plen) = self.parent(sp, ep);
+                sp = psp;
+                ep = pep;
+                len = plen;
+            }
+            visit(i, sp, ep, len);
+        }
+    }
+
+    /// Matching statistics of query : for each position i, the length of
+    /// the longest prefix of query[i..] that occurs in the text. Runs in time
+    /// linear in the length of the query.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let say = sufsort_rs::sufsort::SA::<i32>::new(txt.as_bytes());
+    /// let mx = sufsort_rs::matching::MatchingIndex::new(txt.as_bytes(),
+    ///                                                   &say.sarray);
+    /// let ms = mx.matching_statistics("PISSING".as_bytes());
+    /// assert_eq!(ms, &[2, 4, 3, 2, 1, 0, 0]);
+    /// ```
+    pub fn matching_statistics(&self, query: &[u8]) -> Vec<usize> {
+        let mut ms: Vec<usize> = vec![0; query.len()];
+        self.walk(query, |i, _, _, len| ms[i] = len);
+        ms
+    }
+
+    // whether the occurrence at the given row can not be extended to the
+    // left together with query position i
+    fn left_maximal(&self, query: &[u8], i: usize, row: usize) -> bool {
+        i == 0 || row == self.fm.primary || self.fm.bwt[row] != query[i - 1]
+    }
+
+    /// Maximal exact matches of length at least min_len between the text and
+    /// query, as (text position, query position, length), sorted by query
+    /// position.
+    pub fn mems(&self, query: &[u8],
+                min_len: usize) -> Vec<(usize, usize, usize)> {
+        let min_len = std::cmp::max(min_len, 1);
+        let mut mx: Vec<(usize, usize, usize)> = Vec::new();
+        self.walk(query, |i, sp, ep, len| {
+            if len < min_len {
+                return;
+            }
+            // occurrences of the longest match can not be extended right
+            for row in sp..ep {
+                if self.left_maximal(query, i, row) {
+                    mx.push((self.fm.locate(row), i, len));
+                }
+            }
+            // occurrences only in the parent match exactly its depth
+            let (mut csp, mut cep) = (sp, ep);
+            loop {
+                let (psp, pep, plen) = self.parent(csp, cep);
+                if plen < min_len {
+                    break;
+                }
+                for row in (psp..csp).chain(cep..pep) {
+                    if self.left_maximal(query, i, row) {
+                        mx.push((self.fm.locate(row), i, plen));
+                    }
+                }
+                csp = psp;
+                cep = pep;
+            }
+        });
+        mx.sort_by_key(|x| (x.1, x.0));
+        mx
+    }
+
+    /// Maximal unique matches of length at least min_len between the text
+    /// and query, i.e., maximal matches occurring exactly once in both, as
+    /// (text position, query position, length), sorted by query position.
+    pub fn mums(&self, query: &[u8],
+                min_len: usize) -> Vec<(usize, usize, usize)> {
+        let min_len = std::cmp::max(min_len, 1);
+        let qsa = SA::<i64>::new(query);
+        let qlcp = construct_lcp_phi(query, &qsa.sarray);
+        let mut qisa: Vec<usize> = vec![0; query.len()];
+        for (k, x) in qsa.sarray.iter().enumerate() {
+            qisa[*x as usize] = k;
+        }
+        let mut mx: Vec<(usize, usize, usize)> = Vec::new();
+        self.walk(query, |i, sp, ep, len| {
+            if len < min_len || ep - sp != 1 || !self.left_maximal(query, i, sp) {
+                return;
+            }
+            // unique in the query, if the neighbouring suffixes share less
+            let k = qisa[i];
+            let lx = std::cmp::max(qlcp[k],
+                                   if k + 1 < query.len() { qlcp[k + 1] } else { 0 });
+            if (lx as usize) < len {
+                mx.push((self.fm.locate(sp), i, len));
+            }
+        });
+        mx.sort_by_key(|x| (x.1, x.0));
+        mx
+    }
+}
+
+/// Matches on the forward strand of the query, and on its reverse complement.
+/// All query positions are on the forward query : a reverse match (r, q,
+/// len) means that reference[r..r+len] is the reverse complement of
+/// query[q..q+len]. Both lists are sorted by query position.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct StrandMatches {
+    pub forward: Vec<(usize, usize, usize)>,
+    pub reverse: Vec<(usize, usize, usize)>,
+}
+
+/// Reverse complement of a DNA sequence, as dna::reverse_complement.
+pub fn reverse_complement(seq: &[u8]) -> Vec<u8> {
+    dna::reverse_complement(seq)
+}
+
+// matches with the reverse complement of a query of length m, with their
+// query positions mapped to the forward query
+fn to_forward(m: usize, mut mx: Vec<(usize, usize, usize)>) -> Vec<(usize, usize, usize)> {
+    for x in mx.iter_mut() {
+        x.1 = m - x.1 - x.2;
+    }
+    mx.sort_by_key(|x| (x.1, x.0));
+    mx
+}
+
+/// Maximal exact matches (MEMs) of length at least min_len between reference
+/// and query, as (reference position, query position, length). If revcomp is
+/// set, the matches with the reverse complement of the query are also
+/// reported.
+///
+/// #Example
+///
+/// ```
+/// let mx = sufsort_rs::matching::mems("ACGTTACGA".as_bytes(),
+///                                     "TTACGT".as_bytes(), 3, false);
+/// assert_eq!(mx.forward, &[(3, 0, 5), (0, 2, 4)]);
+/// ```
+pub fn mems(reference: &[u8], query: &[u8], min_len: usize,
+            revcomp: bool) -> StrandMatches {
+    let say = SA::<i64>::new(reference);
+    let mx = MatchingIndex::new(reference, &say.sarray);
+    StrandMatches{
+        forward: mx.mems(query, min_len),
+        reverse: if revcomp {
+            to_forward(query.len(), mx.mems(&reverse_complement(query), min_len))
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+/// Maximal unique matches (MUMs) of length at least min_len between
+/// reference and query, as (reference position, query position, length).
+/// If revcomp is set, the matches with the reverse complement of the query
+/// are also reported.
+pub fn mums(reference: &[u8], query: &[u8], min_len: usize,
+            revcomp: bool) -> StrandMatches {
+    let say = SA::<i64>::new(reference);
+    let mx = MatchingIndex::new(reference, &say.sarray);
+    StrandMatches{
+        forward: mx.mums(query, min_len),
+        reverse: if revcomp {
+            to_forward(query.len(), mx.mums(&reverse_complement(query), min_len))
+        } else {
+            Vec::new()
+        },
+    }
+}
+
+/// Longest common substring of a and b, given as (position in a, position
+/// in b, length). Returns None if a and b have no character in common.
+///
+/// #Example
+///
+/// ```
+/// let lcs = sufsort_rs::matching::longest_common_substring(
+///     "MISSISSIPPI".as_bytes(), "PISSING".as_bytes());
+/// assert_eq!(lcs, Some((4, 1, 4)));
+/// ```
+pub fn longest_common_substring(a: &[u8], b: &[u8])
+        -> Option<(usize, usize, usize)> {
+    let say = SA::<i64>::new(a);
+    let mx = MatchingIndex::new(a, &say.sarray);
+    let mut best: Option<(usize, usize, usize)> = None;
+    mx.walk(b, |i, sp, _, len| {
+        if len > 0 && best.is_none_or(|x| len >= x.2) {
+            best = Some((mx.fm.locate(sp), i, len));
+        }
+    });
+    best
+}
@@ -0,0 +1,256 @@
+use sais::construct_sa_int;
+
+/// Byte written in the BWT in place of the end marker of a string. The
+/// strings themselves must not contain it.
+pub const TERMINATOR: u8 = b'$';
+
+// occurrence counts are sampled every OCC_RATE rows of the BWT
+const OCC_RATE: usize = 64;
+const NO_CODE: usize = 256;
+
+/// Order of the end markers of the strings relative to each other. All the
+/// end markers are smaller than any character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminatorOrder {
+    /// The end marker of the k-th string is the k-th smallest.
+    Input,
+    /// End markers follow the lexicographic order of their strings, with
+    /// equal strings in input order.
+    Lexicographic,
+}
+
+/// BWT of a collection of strings, each terminated by its own end marker.
+/// The character preceding the first character of a string is its own end
+/// marker, so each string is a cycle of the BWT. The suffix array is kept
+/// with entries of type T, e.g., u32 when the concatenation is shorter than
+/// 2^32.
+pub struct MultiBWT<T> {
+    pub bwt: Vec<u8>,
+    /// Suffix array of the concatenation of the terminated strings
+    pub sarray: Vec<T>,
+    /// String id of each end marker, in the order they occur in bwt
+    pub term_ids: Vec<usize>,
+    /// Rank of the end marker of each string, which is also the row of the
+    /// suffix consisting of only that end marker
+    pub term_ranks: Vec<usize>,
+    /// Start of each string in the concatenation, followed by the total
+    /// length of the concatenation, end markers included
+    pub starts: Vec<usize>,
+}
+
+impl<T> MultiBWT<T>
+    where T: std::marker::Copy + num::ToPrimitive + num::FromPrimitive {
+    /// Construct the BWT of strings with the given order of end markers.
+    /// Panics if the total length of the terminated strings does not fit T.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::msbwt::{MultiBWT, TerminatorOrder};
+    /// let strings: Vec<&[u8]> = vec!["CA".as_bytes(), "AC".as_bytes()];
+    /// let mbx = MultiBWT::<u32>::new(&strings, TerminatorOrder::Input);
+    /// assert_eq!(mbx.bwt, "ACC$A$".as_bytes());
+    /// assert_eq!(mbx.term_ids, &[1, 0]);
+    /// let mbx = MultiBWT::<u32>::new(&strings, TerminatorOrder::Lexicographic);
+    /// assert_eq!(mbx.bwt, "CAC$A$".as_bytes());
+    /// assert_eq!(mbx.term_ranks, &[1, 0]);
+    /// ```
+    pub fn new(strings: &[&[u8]], order: TerminatorOrder) -> Self {
+        let k = strings.len();
+        assert!(strings.iter().all(|s| !s.contains(&TERMINATOR)));
+        let mut term_ranks: Vec<usize> = (0..k).collect();
+        if order == TerminatorOrder::Lexicographic {
+            let mut ids: Vec<usize> = (0..k).collect();
+            ids.sort_by_key(|&i| strings[i]);
+            for (r, i) in ids.into_iter().enumerate() {
+                term_ranks[i] = r;
+            }
+        }
+
+        // end markers are the symbols 0..k, and characters are shifted by k
+        let mut text: Vec<usize> = Vec::new();
+        let mut starts: Vec<usize> = Vec::with_capacity(k + 1);
+        for (i, s) in strings.iter().enumerate() {
+            starts.push(text.len());
+            text.extend(s.iter().map(|c| k + *c as usize));
+            text.push(term_ranks[i]);
+        }
+        starts.push(text.len());
+        assert!(T::from_usize(text.len()).is_some(),
+                "MultiBWT: the strings are too long for the suffix array type");
+        let sa = construct_sa_int(&text, k + 255);
+
+        let mut mbx = MultiBWT{bwt: Vec::with_capacity(text.len()), sarray: Vec::new(),
+                               term_ids: Vec::with_capacity(k), term_ranks,
+                               starts};
+        for p in sa.iter() {
+            let (id, offset) = mbx.string_at(*p);
+            if offset == 0 {
+                mbx.bwt.push(TERMINATOR);
+                mbx.term_ids.push(id);
+            } else {
+                mbx.bwt.push((text[*p - 1] - k) as u8);
+            }
+        }
+        drop(text);
+        mbx.sarray = sa.into_iter().map(|p| T::from_usize(p).unwrap()).collect();
+        mbx
+    }
+
+    /// Number of rows, i.e., the total length of the strings and their end
+    /// markers
+    pub fn len(&self) -> usize {
+        self.bwt.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bwt.is_empty()
+    }
+
+    /// Number of strings
+    pub fn count(&self) -> usize {
+        self.term_ranks.len()
+    }
+
+    /// String id and offset of a position of the concatenation
+    pub fn string_at(&self, pos: usize) -> (usize, usize) {
+        let id = match self.starts.binary_search(&pos) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        (id, pos - self.starts[id])
+    }
+}
+
+/// FM-index over the BWT of a collection of strings, to search patterns and
+/// retrieve strings by id.
+pub struct MultiFMIndex<T> {
+    pub mbwt: MultiBWT<T>,
+    // counts[c] : number of rows starting with a symbol smaller than c
+    counts: [usize; 257],
+    code: [usize; 256],
+    sigma: usize,
+    occ: Vec<usize>,
+}
+
+impl<T> MultiFMIndex<T>
+    where T: std::marker::Copy + num::ToPrimitive + num::FromPrimitive {
+    /// Construct the FM-index of strings with the given order of end markers.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::msbwt::{MultiFMIndex, TerminatorOrder};
+    /// let strings: Vec<&[u8]> = vec!["ACGT".as_bytes(), "GGA".as_bytes(),
+    ///                                "TACG".as_bytes()];
+    /// let fmx = MultiFMIndex::<u32>::new(&strings, TerminatorOrder::Input);
+    /// assert_eq!(fmx.read(1), "GGA".as_bytes());
+    /// assert_eq!(fmx.reads_containing("ACG".as_bytes()), &[0, 2]);
+    /// ```
+    pub fn new(strings: &[&[u8]], order: TerminatorOrder) -> Self {
+        let mbwt = MultiBWT::new(strings, order);
+        let mut freq: [usize; 256] = [0; 256];
+        for s in strings.iter() {
+            for c in s.iter() {
+                freq[*c as usize] += 1;
+            }
+        }
+        let mut counts: [usize; 257] = [0; 257];
+        let mut code: [usize; 256] = [NO_CODE; 256];
+        let mut sigma: usize = 0;
+        counts[0] = mbwt.count();
+        for c in 0..256 {
+            counts[c + 1] = counts[c] + freq[c];
+            if freq[c] > 0 {
+                code[c] = sigma;
+                sigma += 1;
+            }
+        }
+
+        let n = mbwt.len();
+        let nblocks = n / OCC_RATE + 1;
+        let mut occ: Vec<usize> = vec![0; nblocks * sigma];
+        let mut cur: Vec<usize> = vec![0; sigma];
+        for (i, c) in mbwt.bwt.iter().enumerate() {
+            if i.is_multiple_of(OCC_RATE) {
+                let b = i / OCC_RATE;
+                occ[b * sigma..(b + 1) * sigma].copy_from_slice(&cur);
+            }
+            if *c != TERMINATOR {
+                cur[code[*c as usize]] += 1;
+            }
+        }
+        if n.is_multiple_of(OCC_RATE) {
+            let b = n / OCC_RATE;
+            occ[b * sigma..(b + 1) * sigma].copy_from_slice(&cur);
+        }
+        MultiFMIndex{mbwt, counts, code, sigma, occ}
+    }
+
+    /// Number of occurrences of the character c in bwt[0..i]
+    pub fn rank(&self, c: u8, i: usize) -> usize {
+        let cx = self.code[c as usize];
+        if cx == NO_CODE || c == TERMINATOR {
+            return 0;
+        }
+        let b = i / OCC_RATE;
+        let mut rx = self.occ[b * self.sigma + cx];
+        for j in (b * OCC_RATE)..i {
+            if self.mbwt.bwt[j] == c {
+                rx += 1;
+            }
+        }
+        rx
+    }
+
+    /// Rows [sp, ep) of the suffixes prefixed by pat
+    pub fn backward_search(&self, pat: &[u8]) -> (usize, usize) {
+        let (mut sp, mut ep) = (0, self.mbwt.len());
+        for c in pat.iter().rev() {
+            if *c == TERMINATOR {
+                return (0, 0);
+            }
+            let cx = self.counts[*c as usize];
+            sp = cx + self.rank(*c, sp);
+            ep = cx + self.rank(*c, ep);
+            if sp >= ep {
+                return (0, 0);
+            }
+        }
+        (sp, ep)
+    }
+
+    /// Occurrences of pat as (string id, offset), sorted.
+    pub fn locate(&self, pat: &[u8]) -> Vec<(usize, usize)> {
+        let (sp, ep) = self.backward_search(pat);
+        let mut px: Vec<(usize, usize)> = (sp..ep)
+            .map(|r| self.mbwt.string_at(self.mbwt.sarray[r].to_usize().unwrap())).collect();
+        px.sort_unstable();
+        px
+    }
+
+    /// Sorted ids of the strings that contain pat
+    pub fn reads_containing(&self, pat: &[u8]) -> Vec<usize> {
+        let mut ids: Vec<usize> = self.locate(pat).into_iter()
+            .map(|x| x.0).collect();
+        ids.dedup();
+        ids
+    }
+
+    /// The string with the given id, spelled backwards from its end marker
+    /// by LF-mapping.
+    pub fn read(&self, id: usize) -> Vec<u8> {
+        let mut r = self.mbwt.term_ranks[id];
+        let mut sx: Vec<u8> = Vec::new();
+        loop {
+            let c = self.mbwt.bwt[r];
+            if c == TERMINATOR {
+                break;
+            }
+            sx.push(c);
+            r = self.counts[c as usize] + self.rank(c, r);
+        }
+        sx.reverse();
+        sx
+    }
+}
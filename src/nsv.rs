@@ -0,0 +1,49 @@
+/// Construct the previous smaller value array of src, i.e., for each i the
+/// largest j < i such that src[j] < src[i]. Positions without a previous
+/// smaller value are set to src.len().
+///
+/// #Example
+/// ```
+/// let lcp: Vec<i32> = vec![0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3];
+/// let psv = sufsort_rs::nsv::construct_psv(&lcp);
+/// assert_eq!(psv, &[11, 0, 0, 2, 11, 11, 5, 11, 7, 7, 9]);
+/// ```
+pub fn construct_psv<T>(src: &[T]) -> Vec<usize>
+    where T: std::cmp::Ord {
+    let n = src.len();
+    let mut psv: Vec<usize> = vec![n; n];
+    for i in 1..n {
+        // follow the chain of previous smaller values of i-1
+        let mut j = i - 1;
+        while j != n && src[j] >= src[i] {
+            j = psv[j];
+        }
+        psv[i] = j;
+    }
+    psv
+}
+
+/// Construct the next smaller value array of src, i.e., for each i the
+/// smallest j > i such that src[j] < src[i]. Positions without a next
+/// smaller value are set to src.len().
+///
+/// #Example
+/// ```
+/// let lcp: Vec<i32> = vec![0, 1, 1, 4, 0, 0, 1, 0, 2, 1, 3];
+/// let nsv = sufsort_rs::nsv::construct_nsv(&lcp);
+/// assert_eq!(nsv, &[11, 4, 4, 4, 11, 11, 7, 11, 9, 11, 11]);
+/// ```
+pub fn construct_nsv<T>(src: &[T]) -> Vec<usize>
+    where T: std::cmp::Ord {
+    let n = src.len();
+    let mut nsv: Vec<usize> = vec![n; n];
+    for i in (0..n).rev() {
+        // follow the chain of next smaller values of i+1
+        let mut j = i + 1;
+        while j < n && src[j] >= src[i] {
+            j = nsv[j];
+        }
+        nsv[i] = j;
+    }
+    nsv
+}
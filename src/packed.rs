@@ -0,0 +1,200 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+use sufsort::{SA, SuffixArray, SuffixArrayMut, SAIter};
+
+// number of bits needed for the values 0..=n
+fn bit_width(n: usize) -> u32 {
+    std::cmp::max(usize::BITS - n.leading_zeros(), 1)
+}
+
+// entries of a little-endian i64 file
+fn read_i64_file<P: AsRef<Path>>(path: P) -> io::Result<(usize, impl Iterator<Item=io::Result<usize>>)> {
+    let file = File::open(path)?;
+    let bytes = file.metadata()?.len() as usize;
+    if !bytes.is_multiple_of(8) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData,
+                                  "file length is not a multiple of 8"));
+    }
+    let mut reader = BufReader::new(file);
+    let entries = (0..bytes / 8).map(move |_| {
+        let mut buf: [u8; 8] = [0; 8];
+        reader.read_exact(&mut buf)?;
+        let x = i64::from_le_bytes(buf);
+        if x < 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "negative entry"));
+        }
+        Ok(x as usize)
+    });
+    Ok((bytes / 8, entries))
+}
+
+/// Array of n values in 0..=n packed in ceil(log2(n+1)) bits each.
+pub struct PackedSA {
+    pub width: u32,
+    n: usize,
+    words: Vec<u64>,
+}
+
+impl PackedSA {
+    /// Array of n zeros, with entries wide enough for the values 0..=n
+    pub fn zeroed(n: usize) -> Self {
+        let width = bit_width(n);
+        PackedSA{width, n, words: vec![0; (n * width as usize).div_ceil(64)]}
+    }
+
+    /// Constructs the packed suffix array of src.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::sufsort::SuffixArray;
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let psa = sufsort_rs::packed::PackedSA::new(txt.as_bytes());
+    /// assert_eq!(psa.width, 4);
+    /// assert_eq!(psa.at(2), 4);
+    /// assert_eq!(sufsort_rs::sufsort::search(txt.as_bytes(), &psa,
+    ///                                        "ISS".as_bytes()), (2, 2));
+    /// ```
+    pub fn new(src: &[u8]) -> Self {
+        PackedSA::from_sa(&SA::<i64>::new(src).sarray)
+    }
+
+    /// Packed copy of the suffix array sa
+    pub fn from_sa<S: SuffixArray + ?Sized>(sa: &S) -> Self {
+        let mut psa = PackedSA::zeroed(sa.len());
+        for i in 0..sa.len() {
+            psa.set(i, sa.at(i));
+        }
+        psa
+    }
+
+    /// Load a suffix array stored as little-endian i64 values, e.g., by
+    /// ExternalSuffixSorter, streaming it into the packed array.
+    pub fn from_i64_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (n, entries) = read_i64_file(path)?;
+        let mut psa = PackedSA::zeroed(n);
+        for (i, x) in entries.enumerate() {
+            let x = x?;
+            if x > n {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "entry out of range"));
+            }
+            psa.set(i, x);
+        }
+        Ok(psa)
+    }
+
+    pub fn iter(&self) -> SAIter<'_, PackedSA> {
+        SAIter::new(self)
+    }
+
+    /// Size of the entries in bytes
+    pub fn size_in_bytes(&self) -> usize {
+        8 * self.words.len()
+    }
+}
+
+impl SuffixArray for PackedSA {
+    fn len(&self) -> usize {
+        self.n
+    }
+
+    fn at(&self, i: usize) -> usize {
+        assert!(i < self.n);
+        let w = self.width as usize;
+        let pos = i * w;
+        let (k, off) = (pos / 64, pos % 64);
+        let mut x = self.words[k] >> off;
+        if off + w > 64 {
+            x |= self.words[k + 1] << (64 - off);
+        }
+        let mask = if w == 64 { u64::MAX } else { (1u64 << w) - 1 };
+        (x & mask) as usize
+    }
+}
+
+impl SuffixArrayMut for PackedSA {
+    fn set(&mut self, i: usize, x: usize) {
+        let w = self.width as usize;
+        assert!(i < self.n && x >> w == 0);
+        let pos = i * w;
+        let (k, off) = (pos / 64, pos % 64);
+        let mask = if w == 64 { u64::MAX } else { (1u64 << w) - 1 };
+        self.words[k] = (self.words[k] & !(mask << off)) | ((x as u64) << off);
+        if off + w > 64 {
+            let hi = off + w - 64;
+            self.words[k + 1] = (self.words[k + 1] & !((1u64 << hi) - 1)) |
+                ((x as u64) >> (64 - off));
+        }
+    }
+}
+
+/// Suffix array with 40-bit (5-byte) entries, for texts shorter than 1 TB.
+pub struct SA40 {
+    bytes: Vec<u8>,
+}
+
+impl SA40 {
+    const MAX: usize = (1 << 40) - 1;
+
+    /// Constructs the 40-bit suffix array of src.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::sufsort::SuffixArray;
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let sa40 = sufsort_rs::packed::SA40::new(txt.as_bytes());
+    /// let sa: Vec<usize> = sa40.iter().collect();
+    /// assert_eq!(sa, &[10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2]);
+    /// ```
+    pub fn new(src: &[u8]) -> Self {
+        SA40::from_sa(&SA::<i64>::new(src).sarray)
+    }
+
+    /// 40-bit copy of the suffix array sa
+    pub fn from_sa<S: SuffixArray + ?Sized>(sa: &S) -> Self {
+        let mut bytes: Vec<u8> = Vec::with_capacity(5 * sa.len());
+        for i in 0..sa.len() {
+            let x = sa.at(i);
+            assert!(x <= SA40::MAX);
+            bytes.extend_from_slice(&(x as u64).to_le_bytes()[..5]);
+        }
+        SA40{bytes}
+    }
+
+    /// Load a suffix array stored as little-endian i64 values, e.g., by
+    /// ExternalSuffixSorter, streaming it into 40-bit entries.
+    pub fn from_i64_file<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let (n, entries) = read_i64_file(path)?;
+        let mut bytes: Vec<u8> = Vec::with_capacity(5 * n);
+        for x in entries {
+            let x = x?;
+            if x > SA40::MAX {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "entry out of range"));
+            }
+            bytes.extend_from_slice(&(x as u64).to_le_bytes()[..5]);
+        }
+        Ok(SA40{bytes})
+    }
+
+    pub fn iter(&self) -> SAIter<'_, SA40> {
+        SAIter::new(self)
+    }
+
+    /// Size of the entries in bytes
+    pub fn size_in_bytes(&self) -> usize {
+        self.bytes.len()
+    }
+}
+
+impl SuffixArray for SA40 {
+    fn len(&self) -> usize {
+        self.bytes.len() / 5
+    }
+
+    fn at(&self, i: usize) -> usize {
+        let b = &self.bytes[5 * i..5 * i + 5];
+        u64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], 0, 0, 0]) as usize
+    }
+}
@@ -0,0 +1,104 @@
+use regex_automata::Anchored;
+use regex_automata::dfa::Automaton;
+use regex_automata::dfa::dense::{self, BuildError};
+use regex_automata::util::primitives::StateID;
+use regex_automata::util::start;
+
+// character at depth d of the suffix at sa[i], None past the end
+fn char_at<T: num::ToPrimitive>(txt: &[u8], sa: &[T], i: usize, d: usize) -> Option<u8> {
+    txt.get(sa[i].to_usize().unwrap() + d).cloned()
+}
+
+// anchored start state of a match preceded by the byte before
+fn start_state<A: Automaton>(dfa: &A, before: Option<u8>) -> StateID {
+    let config = start::Config::new().anchored(Anchored::Yes).look_behind(before);
+    dfa.start_state(&config).expect("regex_search: the DFA must support anchored searches")
+}
+
+/// Sorted text positions p such that the DFA dfa matches a substring of txt
+/// starting at p, given the suffix array sa of txt. The suffix tree is
+/// emulated on sa : the suffixes sharing a prefix are fed to the DFA once,
+/// a subtree is pruned as soon as the DFA reaches its dead state, and all
+/// its suffixes are reported as soon as it reaches a match state.
+///
+/// The DFA is started with the byte preceding each suffix as look-behind,
+/// so that `^`, `(?m:^)` and `\b` are evaluated in the context of the text.
+/// The walk is done once for each distinct start state. Panics if dfa has
+/// no anchored start state, or on a quit byte.
+pub fn regex_search_dfa<A: Automaton, T>(txt: &[u8], sa: &[T], dfa: &A) -> Vec<usize>
+    where T: num::ToPrimitive {
+    assert!(txt.len() == sa.len());
+    let n = txt.len();
+    // start state after no byte, then after each byte
+    let starts: Vec<StateID> = std::iter::once(start_state(dfa, None))
+        .chain((0..=255u8).map(|b| start_state(dfa, Some(b)))).collect();
+    let start_of = |p: usize| -> StateID {
+        if p == 0 { starts[0] } else { starts[1 + txt[p - 1] as usize] }
+    };
+    let mut distinct: Vec<StateID> = Vec::new();
+    for s in &starts {
+        if !distinct.contains(s) {
+            distinct.push(*s);
+        }
+    }
+
+    let mut occ: Vec<usize> = Vec::new();
+    for s0 in distinct {
+        let report = |l: usize, r: usize, occ: &mut Vec<usize>| {
+            occ.extend(sa[l..r].iter().map(|x| x.to_usize().unwrap())
+                       .filter(|p| start_of(*p) == s0));
+        };
+        // (l, r, d, s) : the suffixes [l, r) share a prefix of length d,
+        // after which the DFA is in state s
+        let mut stack: Vec<(usize, usize, usize, StateID)> = vec![(0, n, 0, s0)];
+        while let Some((l, r, d, s)) = stack.pop() {
+            let mut i = l;
+            if i < r && char_at(txt, sa, i, d).is_none() {
+                if dfa.is_match_state(dfa.next_eoi_state(s)) {
+                    report(i, i + 1, &mut occ);
+                }
+                i += 1;
+            }
+            while i < r {
+                let c = char_at(txt, sa, i, d).unwrap();
+                let (mut lo, mut hi) = (i + 1, r);
+                while lo < hi {
+                    let mid = lo + (hi - lo) / 2;
+                    if char_at(txt, sa, mid, d) == Some(c) { lo = mid + 1; } else { hi = mid; }
+                }
+                let sx = dfa.next_state(s, c);
+                assert!(!dfa.is_quit_state(sx), "regex_search: quit byte {}", c);
+                if dfa.is_match_state(sx) {
+                    report(i, lo, &mut occ);
+                } else if !dfa.is_dead_state(sx) {
+                    stack.push((i, lo, d + 1, sx));
+                }
+                i = lo;
+            }
+        }
+    }
+    occ.sort_unstable();
+    occ
+}
+
+/// Sorted text positions where a match of the regular expression re
+/// starts, as regex_search_dfa with a dense DFA compiled from re. Dense
+/// DFAs do not support Unicode word boundaries; use `(?-u:\b)` instead.
+///
+/// #Example
+///
+/// ```
+/// let txt = "ERROR a\nWARN ERROR b\nERROR c".to_string();
+/// let say = sufsort_rs::sufsort::SA::<i64>::new(txt.as_bytes());
+/// let occ = sufsort_rs::regex::regex_search(txt.as_bytes(), &say.sarray,
+///                                           r"(?m:^)ERROR [a-c]").unwrap();
+/// assert_eq!(occ, &[0, 21]);
+/// let occ = sufsort_rs::regex::regex_search(txt.as_bytes(), &say.sarray,
+///                                           r"R+ [bc]").unwrap();
+/// assert_eq!(occ, &[17, 25]);
+/// ```
+pub fn regex_search<T>(txt: &[u8], sa: &[T], re: &str) -> Result<Vec<usize>, Box<BuildError>>
+    where T: num::ToPrimitive {
+    let dfa = dense::DFA::new(re).map_err(Box::new)?;
+    Ok(regex_search_dfa(txt, sa, &dfa))
+}
@@ -0,0 +1,154 @@
+use lcp::lcp_intervals;
+
+/// A repeated substring of the text, given by its length and the sorted
+/// list of its starting positions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repeat {
+    pub length: usize,
+    pub positions: Vec<usize>,
+}
+
+impl Repeat {
+    fn from_interval<T>(sa: &[T], length: usize,
+                        left: usize, right: usize) -> Repeat
+        where T: num::ToPrimitive {
+        let mut positions: Vec<usize> = sa[left..right + 1].iter()
+            .map(|x| x.to_usize().unwrap()).collect();
+        positions.sort_unstable();
+        Repeat{length, positions}
+    }
+}
+
+/// Longest substring of the text that occurs at least twice, along with all
+/// its occurrences. sa and lcp are the suffix and LCP arrays of the text.
+/// Returns None if no character of the text is repeated.
+///
+/// #Example
+///
+/// ```
+/// let txt = "MISSISSIPPI".to_string();
+/// let say = sufsort_rs::sufsort::SA::<i32>::new(txt.as_bytes());
+/// let lcp = sufsort_rs::lcp::construct_lcp_phi(txt.as_bytes(), &say.sarray);
+/// let lrs = sufsort_rs::repeats::longest_repeated_substring(&say.sarray, &lcp);
+/// assert_eq!(lrs.unwrap().positions, &[1, 4]);
+/// ```
+pub fn longest_repeated_substring<T>(sa: &[T], lcp: &[T]) -> Option<Repeat>
+    where T: num::ToPrimitive {
+    assert!(sa.len() == lcp.len());
+    let mut mpos: usize = 0;
+    let mut mlen: usize = 0;
+    for (i, x) in lcp.iter().enumerate().skip(1) {
+        let lx = x.to_usize().unwrap();
+        if lx > mlen {
+            mlen = lx;
+            mpos = i;
+        }
+    }
+    if mlen == 0 {
+        return None;
+    }
+    // extend to the lcp-interval of the maximum
+    let mut left = mpos - 1;
+    while left > 0 && lcp[left].to_usize().unwrap() >= mlen {
+        left -= 1;
+    }
+    let mut right = mpos;
+    while right + 1 < lcp.len() && lcp[right + 1].to_usize().unwrap() >= mlen {
+        right += 1;
+    }
+    Some(Repeat::from_interval(sa, mlen, left, right))
+}
+
+// Prefix sums of the positions k at which the suffixes SA[k-1] and SA[k]
+// are preceded by different characters, or one of them is the whole text.
+fn left_diverse_sums<T>(text: &[u8], sa: &[T]) -> Vec<usize>
+    where T: num::ToPrimitive {
+    let n = sa.len();
+    let mut lds: Vec<usize> = vec![0; n + 1];
+    for k in 1..n {
+        let s1 = sa[k - 1].to_usize().unwrap();
+        let s2 = sa[k].to_usize().unwrap();
+        let dx = s1 == 0 || s2 == 0 || text[s1 - 1] != text[s2 - 1];
+        lds[k + 1] = lds[k] + if dx { 1 } else { 0 };
+    }
+    lds
+}
+
+/// All the maximal repeats of length at least min_len, i.e., the repeated
+/// substrings that can be extended neither to the left nor to the right
+/// without losing an occurrence. Repeats are reported in the bottom-up order
+/// of their lcp-intervals.
+///
+/// #Example
+///
+/// ```
+/// let txt = "MISSISSIPPI".to_string();
+/// let say = sufsort_rs::sufsort::SA::<i32>::new(txt.as_bytes());
+/// let lcp = sufsort_rs::lcp::construct_lcp_phi(txt.as_bytes(), &say.sarray);
+/// let rpx = sufsort_rs::repeats::maximal_repeats(txt.as_bytes(),
+///                                                &say.sarray, &lcp, 1);
+/// let lens: Vec<usize> = rpx.iter().map(|x| x.length).collect();
+/// assert_eq!(lens, &[4, 1, 1, 1]); // ISSI, I, P, S
+/// ```
+pub fn maximal_repeats<T>(text: &[u8], sa: &[T], lcp: &[T],
+                          min_len: usize) -> Vec<Repeat>
+    where T: num::ToPrimitive {
+    assert!(text.len() == sa.len() && sa.len() == lcp.len());
+    let lds = left_diverse_sums(text, sa);
+    lcp_intervals(lcp)
+        .filter(|&(lv, l, r)| {
+            lv > 0 && lv >= min_len && lds[r + 1] - lds[l + 1] > 0
+        })
+        .map(|(lv, l, r)| Repeat::from_interval(sa, lv, l, r))
+        .collect()
+}
+
+/// All the supermaximal repeats of length at least min_len, i.e., the maximal
+/// repeats that do not occur as a substring of any other maximal repeat.
+///
+/// #Example
+///
+/// ```
+/// let txt = "MISSISSIPPI".to_string();
+/// let say = sufsort_rs::sufsort::SA::<i32>::new(txt.as_bytes());
+/// let lcp = sufsort_rs::lcp::construct_lcp_phi(txt.as_bytes(), &say.sarray);
+/// let rpx = sufsort_rs::repeats::supermaximal_repeats(txt.as_bytes(),
+///                                                     &say.sarray, &lcp, 1);
+/// assert_eq!(rpx[0].positions, &[1, 4]); // ISSI
+/// assert_eq!(rpx[1].positions, &[8, 9]); // P
+/// ```
+pub fn supermaximal_repeats<T>(text: &[u8], sa: &[T], lcp: &[T],
+                               min_len: usize) -> Vec<Repeat>
+    where T: num::ToPrimitive {
+    assert!(text.len() == sa.len() && sa.len() == lcp.len());
+    let mut rpx: Vec<Repeat> = Vec::new();
+    // left boundary of the previous interval; the intervals nested in
+    // [l..r] are reported right before it, so [l..r] is a local maximum
+    // iff the previous interval is not nested in it.
+    let mut prev_left: Option<usize> = None;
+    for (lv, l, r) in lcp_intervals(lcp) {
+        let local_max = prev_left.is_none_or(|x| x < l);
+        prev_left = Some(l);
+        if !local_max || lv == 0 || lv < min_len || r - l + 1 > 257 {
+            continue;
+        }
+        // all the left characters should be distinct
+        let mut seen = [false; 256];
+        let mut distinct = true;
+        for x in sa[l..r + 1].iter() {
+            let sx = x.to_usize().unwrap();
+            if sx > 0 {
+                let cx = text[sx - 1] as usize;
+                if seen[cx] {
+                    distinct = false;
+                    break;
+                }
+                seen[cx] = true;
+            }
+        }
+        if distinct {
+            rpx.push(Repeat::from_interval(sa, lv, l, r));
+        }
+    }
+    rpx
+}
@@ -0,0 +1,97 @@
+use sufsort::{SA, construct_isa};
+use lcp::construct_lcp_phi;
+use lyndon::lyndon_array;
+use lce::LCE;
+
+/// A run, i.e., a maximal periodic substring text[start..start+length] whose
+/// smallest period is period, and length >= 2 * period.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub start: usize,
+    pub length: usize,
+    pub period: usize,
+}
+
+impl Run {
+    /// Number of full copies of the period
+    pub fn copies(&self) -> usize {
+        self.length / self.period
+    }
+}
+
+/// All the runs of text, sorted by start position and period.
+///
+/// Every run has a Lyndon root that is the longest Lyndon word starting at
+/// its position, with respect to either the alphabet order or its reverse
+/// (Bannai et al., 2017). Candidate periods are thus the values of the
+/// Lyndon arrays for both orders, and each one is extended to the left and
+/// the right with LCE queries on the reversed and forward text.
+///
+/// #Example
+///
+/// ```
+/// let rx = sufsort_rs::runs::runs("aabaabaab".as_bytes());
+/// let rx: Vec<(usize, usize, usize)> =
+///     rx.iter().map(|x| (x.start, x.length, x.period)).collect();
+/// assert_eq!(rx, &[(0, 2, 1), (0, 9, 3), (3, 2, 1), (6, 2, 1)]);
+/// ```
+pub fn runs(text: &[u8]) -> Vec<Run> {
+    let n = text.len();
+    let mut rx: Vec<Run> = Vec::new();
+    if n < 2 {
+        return rx;
+    }
+    let say = SA::<i64>::new(text);
+    let lcp = construct_lcp_phi(text, &say.sarray);
+    let lce = LCE::new(&say.sarray, &lcp);
+    let rev: Vec<u8> = text.iter().rev().cloned().collect();
+    let rsa = SA::<i64>::new(&rev);
+    let rlcp = construct_lcp_phi(&rev, &rsa.sarray);
+    let rlce = LCE::new(&rsa.sarray, &rlcp);
+
+    for reverse in [false, true].iter() {
+        let key: Vec<u8> = if *reverse {
+            text.iter().map(|c| 255 - c).collect()
+        } else {
+            text.to_vec()
+        };
+        let ksa = SA::<i64>::new(&key);
+        let lyn = lyndon_array(&construct_isa(&ksa.sarray));
+        for (i, period) in lyn.into_iter().enumerate() {
+            let j = i + period;
+            if j == n {
+                continue;
+            }
+            let right = lce.lce(i, j);
+            let left = if i > 0 { rlce.lce(n - i, n - j) } else { 0 };
+            if left + right >= period {
+                rx.push(Run{start: i - left, length: period + left + right,
+                            period});
+            }
+        }
+    }
+    rx.sort_by_key(|x| (x.start, x.period));
+    rx.dedup();
+    rx
+}
+
+/// Tandem repeats of text, as the runs with a period between min_period and
+/// max_period (inclusive) that contain at least min_copies full copies of
+/// the period, e.g., microsatellites for periods up to 6.
+///
+/// #Example
+///
+/// ```
+/// let rx = sufsort_rs::runs::tandem_repeats("GCACACACAGT".as_bytes(), 2, 6, 3);
+/// assert_eq!(rx.len(), 1);
+/// assert_eq!((rx[0].start, rx[0].length, rx[0].period), (1, 8, 2));
+/// ```
+pub fn tandem_repeats(text: &[u8], min_period: usize, max_period: usize,
+                      min_copies: usize) -> Vec<Run> {
+    runs(text).into_iter()
+        .filter(|x| {
+            x.period >= min_period && x.period <= max_period &&
+                x.copies() >= min_copies
+        })
+        .collect()
+}
@@ -0,0 +1,125 @@
+const NONE: usize = usize::MAX;
+
+/// Construct the suffix array of a text over the integer alphabet
+/// [0, upper] by induced sorting (SA-IS, Nong, Zhang and Chan, 2009), in
+/// linear time. As with libdivsufsort, a suffix is smaller than any longer
+/// suffix it is a prefix of, so no sentinel needs to be appended.
+///
+/// #Example
+///
+/// ```
+/// let txt: Vec<usize> = "MISSISSIPPI".bytes().map(|c| c as usize).collect();
+/// let sa = sufsort_rs::sais::construct_sa_int(&txt, 255);
+/// assert_eq!(sa, &[10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2]);
+/// ```
+pub fn construct_sa_int(text: &[usize], upper: usize) -> Vec<usize> {
+    let n = text.len();
+    match n {
+        0 => return Vec::new(),
+        1 => return vec![0],
+        2 => return if text[0] < text[1] { vec![0, 1] } else { vec![1, 0] },
+        _ => (),
+    }
+    assert!(text.iter().all(|c| *c <= upper));
+
+    // ls[i] : whether the suffix i is S-type
+    let mut ls: Vec<bool> = vec![false; n];
+    for i in (0..n - 1).rev() {
+        ls[i] = if text[i] == text[i + 1] { ls[i + 1] } else { text[i] < text[i + 1] };
+    }
+    // bucket boundaries : sum_l[c] is the start of the bucket of c, and
+    // sum_s[c] the start of its S-type part
+    let mut sum_l: Vec<usize> = vec![0; upper + 1];
+    let mut sum_s: Vec<usize> = vec![0; upper + 1];
+    for i in 0..n {
+        if !ls[i] {
+            sum_s[text[i]] += 1;
+        } else {
+            sum_l[text[i] + 1] += 1;
+        }
+    }
+    for c in 0..upper + 1 {
+        sum_s[c] += sum_l[c];
+        if c < upper {
+            sum_l[c + 1] += sum_s[c];
+        }
+    }
+
+    let mut lms_map: Vec<usize> = vec![NONE; n + 1];
+    let mut lms: Vec<usize> = Vec::new();
+    for i in 1..n {
+        if !ls[i - 1] && ls[i] {
+            lms_map[i] = lms.len();
+            lms.push(i);
+        }
+    }
+    let m = lms.len();
+    let mut sa: Vec<usize> = vec![NONE; n];
+    induce(text, &ls, &sum_l, &sum_s, &lms, &mut sa);
+
+    if m > 0 {
+        // name the LMS substrings in their induced order, and sort the LMS
+        // suffixes recursively on the reduced text
+        let sorted_lms: Vec<usize> = sa.iter().cloned()
+            .filter(|&v| lms_map[v] != NONE).collect();
+        let mut rec_text: Vec<usize> = vec![0; m];
+        let mut rec_upper: usize = 0;
+        for i in 1..m {
+            let (mut l, mut r) = (sorted_lms[i - 1], sorted_lms[i]);
+            let end_l = if lms_map[l] + 1 < m { lms[lms_map[l] + 1] } else { n };
+            let end_r = if lms_map[r] + 1 < m { lms[lms_map[r] + 1] } else { n };
+            let mut same = end_l - l == end_r - r;
+            if same {
+                while l < end_l && text[l] == text[r] {
+                    l += 1;
+                    r += 1;
+                }
+                same = l != n && text[l] == text[r];
+            }
+            if !same {
+                rec_upper += 1;
+            }
+            rec_text[lms_map[sorted_lms[i]]] = rec_upper;
+        }
+        let rec_sa = construct_sa_int(&rec_text, rec_upper);
+        let sorted_lms: Vec<usize> = rec_sa.iter().map(|&x| lms[x]).collect();
+        induce(text, &ls, &sum_l, &sum_s, &sorted_lms, &mut sa);
+    }
+    sa
+}
+
+// induce the order of all the suffixes from the given order of LMS suffixes
+fn induce(text: &[usize], ls: &[bool], sum_l: &[usize], sum_s: &[usize],
+          lms: &[usize], sa: &mut [usize]) {
+    let n = text.len();
+    for x in sa.iter_mut() {
+        *x = NONE;
+    }
+    let mut buf: Vec<usize> = sum_s.to_vec();
+    for &d in lms.iter() {
+        if d != n {
+            sa[buf[text[d]]] = d;
+            buf[text[d]] += 1;
+        }
+    }
+    // L-type suffixes, left to right
+    buf.copy_from_slice(sum_l);
+    sa[buf[text[n - 1]]] = n - 1;
+    buf[text[n - 1]] += 1;
+    for i in 0..n {
+        let v = sa[i];
+        if v != NONE && v >= 1 && !ls[v - 1] {
+            sa[buf[text[v - 1]]] = v - 1;
+            buf[text[v - 1]] += 1;
+        }
+    }
+    // S-type suffixes, right to left
+    buf.copy_from_slice(sum_l);
+    for i in (0..n).rev() {
+        let v = sa[i];
+        if v != NONE && v >= 1 && ls[v - 1] {
+            buf[text[v - 1] + 1] -= 1;
+            sa[buf[text[v - 1] + 1]] = v - 1;
+        }
+    }
+}
@@ -0,0 +1,273 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use flate2::read::MultiGzDecoder;
+use msbwt::TERMINATOR;
+
+/// Byte written after each sequence in a SeqText, so that no match spans
+/// two sequences. Sequences must not contain it.
+pub const SEPARATOR: u8 = TERMINATOR;
+
+/// A FASTA or FASTQ record. name is the header up to the first whitespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub name: String,
+    pub seq: Vec<u8>,
+    /// Quality string of a FASTQ record
+    pub qual: Option<Vec<u8>>,
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+fn record_name(header: &str) -> String {
+    header.split_whitespace().next().unwrap_or("").to_string()
+}
+
+// read a line without its line terminator; false at the end of input
+fn read_line<R: BufRead>(reader: &mut R, line: &mut String) -> io::Result<bool> {
+    line.clear();
+    if reader.read_line(line)? == 0 {
+        return Ok(false);
+    }
+    while line.ends_with('\n') || line.ends_with('\r') {
+        line.pop();
+    }
+    Ok(true)
+}
+
+/// Iterator over the records of a FASTA file. Sequence lines are
+/// concatenated, and lines starting with ';' are skipped.
+pub struct FastaReader<R> {
+    reader: R,
+    line: String,
+    header: Option<String>,
+}
+
+impl<R: BufRead> FastaReader<R> {
+    pub fn new(reader: R) -> Self {
+        FastaReader{reader, line: String::new(), header: None}
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<Record>> {
+        if self.header.is_none() {
+            loop {
+                if !read_line(&mut self.reader, &mut self.line)? {
+                    return Ok(None);
+                }
+                if self.line.starts_with('>') {
+                    self.header = Some(self.line[1..].to_string());
+                    break;
+                }
+                if !self.line.trim().is_empty() && !self.line.starts_with(';') {
+                    return Err(invalid("FASTA: sequence before the first header"));
+                }
+            }
+        }
+        let name = record_name(&self.header.take().unwrap());
+        let mut seq: Vec<u8> = Vec::new();
+        while read_line(&mut self.reader, &mut self.line)? {
+            if self.line.starts_with('>') {
+                self.header = Some(self.line[1..].to_string());
+                break;
+            }
+            if !self.line.starts_with(';') {
+                seq.extend(self.line.bytes().filter(|c| !c.is_ascii_whitespace()));
+            }
+        }
+        Ok(Some(Record{name, seq, qual: None}))
+    }
+}
+
+impl<R: BufRead> Iterator for FastaReader<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        self.next_record().transpose()
+    }
+}
+
+/// Iterator over the records of a FASTQ file, with one line each for the
+/// sequence and the qualities.
+pub struct FastqReader<R> {
+    reader: R,
+    line: String,
+}
+
+impl<R: BufRead> FastqReader<R> {
+    pub fn new(reader: R) -> Self {
+        FastqReader{reader, line: String::new()}
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<Record>> {
+        loop {
+            if !read_line(&mut self.reader, &mut self.line)? {
+                return Ok(None);
+            }
+            if !self.line.trim().is_empty() {
+                break;
+            }
+        }
+        if !self.line.starts_with('@') {
+            return Err(invalid("FASTQ: header does not start with '@'"));
+        }
+        let name = record_name(&self.line[1..]);
+        if !read_line(&mut self.reader, &mut self.line)? {
+            return Err(invalid("FASTQ: truncated record"));
+        }
+        let seq = self.line.as_bytes().to_vec();
+        if !read_line(&mut self.reader, &mut self.line)? || !self.line.starts_with('+') {
+            return Err(invalid("FASTQ: missing '+' line"));
+        }
+        if !read_line(&mut self.reader, &mut self.line)? || self.line.len() != seq.len() {
+            return Err(invalid("FASTQ: quality length differs from sequence length"));
+        }
+        Ok(Some(Record{name, seq, qual: Some(self.line.as_bytes().to_vec())}))
+    }
+}
+
+impl<R: BufRead> Iterator for FastqReader<R> {
+    type Item = io::Result<Record>;
+
+    fn next(&mut self) -> Option<io::Result<Record>> {
+        self.next_record().transpose()
+    }
+}
+
+/// Open path for reading, decompressing it if it is gzip-compressed.
+pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Box<dyn BufRead>> {
+    let mut reader = BufReader::new(File::open(path)?);
+    if reader.fill_buf()?.starts_with(&[0x1f, 0x8b]) {
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+/// Iterator over the records of the FASTA or FASTQ file at path, possibly
+/// gzip-compressed, read as they are consumed. The format is detected from
+/// the first character.
+pub fn records<P: AsRef<Path>>(path: P)
+        -> io::Result<Box<dyn Iterator<Item=io::Result<Record>>>> {
+    let mut reader = open(path)?;
+    let first = reader.fill_buf()?.first().cloned();
+    match first {
+        None => Ok(Box::new(std::iter::empty())),
+        Some(b'@') => Ok(Box::new(FastqReader::new(reader))),
+        Some(_) => Ok(Box::new(FastaReader::new(reader))),
+    }
+}
+
+/// Read the FASTA or FASTQ file at path, possibly gzip-compressed, as
+/// records does.
+pub fn read_records<P: AsRef<Path>>(path: P) -> io::Result<Vec<Record>> {
+    records(path)?.collect()
+}
+
+/// Concatenation of sequences, each followed by SEPARATOR, with their
+/// names and offsets, to be indexed as a single text.
+#[derive(Debug, Clone)]
+pub struct SeqText {
+    pub text: Vec<u8>,
+    pub names: Vec<String>,
+    /// Start of each sequence in text, followed by text.len()
+    pub offsets: Vec<usize>,
+}
+
+impl Default for SeqText {
+    fn default() -> Self {
+        SeqText::new()
+    }
+}
+
+impl SeqText {
+    pub fn new() -> Self {
+        SeqText{text: Vec::new(), names: Vec::new(), offsets: vec![0]}
+    }
+
+    /// Concatenation of records
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::seqio::{FastaReader, SeqText};
+    /// let fasta = ">chr1 first\nACGT\nAC\n>chr2\nGACG\n".to_string();
+    /// let records = FastaReader::new(fasta.as_bytes());
+    /// let stx = SeqText::from_records(records).unwrap();
+    /// assert_eq!(stx.text, "ACGTAC$GACG$".as_bytes());
+    /// let say = sufsort_rs::sufsort::SA::<i64>::new(&stx.text);
+    /// let (left, count) = say.search_sa("ACG".as_bytes());
+    /// assert_eq!(stx.hits(&say.sarray, left as usize, count as usize),
+    ///            &[("chr1", 0), ("chr2", 1)]);
+    /// ```
+    pub fn from_records<I: IntoIterator<Item=io::Result<Record>>>(records: I) -> io::Result<Self> {
+        let mut stx = SeqText::new();
+        for rec in records {
+            let rec = rec?;
+            if rec.seq.contains(&SEPARATOR) {
+                return Err(invalid("sequence contains the separator"));
+            }
+            stx.push(&rec.name, &rec.seq);
+        }
+        Ok(stx)
+    }
+
+    /// Load the FASTA or FASTQ file at path, possibly gzip-compressed. The
+    /// records are appended as they are read, so that only the text is held
+    /// in memory.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        SeqText::from_records(records(path)?)
+    }
+
+    /// Append the sequence seq named name
+    pub fn push(&mut self, name: &str, seq: &[u8]) {
+        assert!(!seq.contains(&SEPARATOR));
+        self.names.push(name.to_string());
+        self.text.extend_from_slice(seq);
+        self.text.push(SEPARATOR);
+        self.offsets.push(self.text.len());
+    }
+
+    /// Number of sequences
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Sequence id
+    pub fn sequence(&self, id: usize) -> &[u8] {
+        &self.text[self.offsets[id]..self.offsets[id + 1] - 1]
+    }
+
+    /// Sequences, e.g., for MultiBWT::new
+    pub fn sequences(&self) -> Vec<&[u8]> {
+        (0..self.len()).map(|id| self.sequence(id)).collect()
+    }
+
+    /// (sequence id, offset) of the text position pos. None if pos holds a
+    /// separator.
+    pub fn resolve(&self, pos: usize) -> Option<(usize, usize)> {
+        assert!(pos < self.text.len());
+        let id = self.offsets.partition_point(|x| *x <= pos) - 1;
+        if pos + 1 == self.offsets[id + 1] {
+            None
+        } else {
+            Some((id, pos - self.offsets[id]))
+        }
+    }
+
+    /// (sequence name, offset) of the occurrences in the range (left, count)
+    /// of the suffix array sa of text, as returned by search_sa, sorted by
+    /// text position.
+    pub fn hits<T: num::ToPrimitive>(&self, sa: &[T], left: usize, count: usize) -> Vec<(&str, usize)> {
+        let mut pos: Vec<usize> = sa[left..left + count].iter()
+            .map(|x| x.to_usize().unwrap()).collect();
+        pos.sort_unstable();
+        pos.into_iter().filter_map(|p| self.resolve(p))
+            .map(|(id, off)| (self.names[id].as_str(), off)).collect()
+    }
+}
@@ -0,0 +1,209 @@
+use sufsort::search_by;
+use sais::construct_sa_int;
+
+// block i of the sorted selected positions px : the text from px[i] to the
+// next selected position, or to the end
+fn block<'a>(src: &'a [u8], px: &[usize], i: usize) -> &'a [u8] {
+    let end = if i + 1 < px.len() { px[i + 1] } else { src.len() };
+    &src[px[i]..end]
+}
+
+// length of the longest common prefix of x and y
+fn common_prefix(x: &[u8], y: &[u8]) -> usize {
+    x.iter().zip(y.iter()).take_while(|(a, b)| a == b).count()
+}
+
+// string of the blocks of px over the meta-alphabet of their ranks among the
+// distinct blocks, and the largest rank. The suffixes of the selected
+// positions then sort as the suffixes of that string, unless a block other
+// than the last one is a proper prefix of another block, in which case None
+// is returned. In the sorted order of the blocks, such a block is followed
+// by one it is a prefix of.
+fn reduce(src: &[u8], px: &[usize]) -> Option<(Vec<usize>, usize)> {
+    let k = px.len();
+    let mut order: Vec<usize> = (0..k).collect();
+    order.sort_unstable_by(|a, b| block(src, px, *a).cmp(block(src, px, *b)));
+    let mut meta: Vec<usize> = vec![0; k];
+    let mut rank: usize = 0;
+    // whether the group of the blocks equal to the current one has a block
+    // other than the last
+    let mut inner = false;
+    for (t, &i) in order.iter().enumerate() {
+        if t > 0 {
+            let (x, y) = (block(src, px, order[t - 1]), block(src, px, i));
+            if x != y {
+                if inner && y.starts_with(x) {
+                    return None;
+                }
+                rank += 1;
+                inner = false;
+            }
+        }
+        inner |= i + 1 < k;
+        meta[i] = rank;
+    }
+    Some((meta, rank))
+}
+
+/// Sparse suffix array : the suffixes of txt starting at a selected set of
+/// positions, e.g., word starts or every k-th position, in lexicographic
+/// order. Only the selected suffixes are stored.
+pub struct SparseSA<'s, T> {
+    pub txt: &'s [u8],
+    pub sarray: Vec<T>,
+}
+
+impl<'s, T> SparseSA<'s, T>
+    where T: std::marker::Copy + std::cmp::Ord +
+             num::ToPrimitive + num::FromPrimitive + num::Zero {
+
+    /// Constructs the sparse suffix array of the suffixes of src starting at
+    /// the given positions. Duplicate positions are ignored.
+    ///
+    /// The blocks of text between consecutive positions are renamed by their
+    /// ranks, and the suffixes of the renamed string are sorted with
+    /// sais::construct_sa_int, using O(k) words of memory for k positions.
+    /// Renaming takes O(k log k) block comparisons, i.e., O(n log k) time for
+    /// every k-th position. This is exact unless a block, other than the
+    /// last, is a proper prefix of another one (e.g., words followed by
+    /// different numbers of spaces); the suffixes are then sorted by direct
+    /// comparison, which takes O(n k log k) time in the worst case.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let ssa = sufsort_rs::sparse::SparseSA::<i32>::new(txt.as_bytes(), &[0, 3, 6, 9]);
+    /// assert_eq!(ssa.sarray, &[0, 9, 6, 3]); // MISSISSIPPI, PI, SIPPI, SISSIPPI
+    /// ```
+    pub fn new(src: &'s [u8], positions: &[usize]) -> Self {
+        let mut px: Vec<usize> = positions.to_vec();
+        px.sort_unstable();
+        px.dedup();
+        assert!(px.last().is_none_or(|x| *x < src.len()));
+        let sorted: Vec<usize> = match reduce(src, &px) {
+            Some((meta, upper)) => {
+                construct_sa_int(&meta, upper).into_iter().map(|i| px[i]).collect()
+            }
+            None => {
+                px.sort_unstable_by(|a, b| src[*a..].cmp(&src[*b..]));
+                px
+            }
+        };
+        SparseSA{txt: src, sarray: sorted.into_iter().map(|x| T::from_usize(x).unwrap()).collect()}
+    }
+
+    /// Constructs the sparse suffix array of the suffixes of src starting at
+    /// the positions i for which select(src, i) holds.
+    pub fn with_predicate<F>(src: &'s [u8], select: F) -> Self
+        where F: Fn(&[u8], usize) -> bool {
+        let px: Vec<usize> = (0..src.len()).filter(|&i| select(src, i)).collect();
+        Self::new(src, &px)
+    }
+
+    /// Constructs the sparse suffix array of the suffixes of src starting at
+    /// every k-th position.
+    pub fn every_kth(src: &'s [u8], k: usize) -> Self {
+        assert!(k > 0);
+        let px: Vec<usize> = (0..src.len()).step_by(k).collect();
+        Self::new(src, &px)
+    }
+
+    /// Constructs the sparse suffix array of the suffixes of src starting at
+    /// word boundaries, i.e., an alphanumeric char not preceded by one.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// let txt = "to be, or not to be".to_string();
+    /// let ssa = sufsort_rs::sparse::SparseSA::<i32>::word_starts(txt.as_bytes());
+    /// assert_eq!(ssa.sarray, &[17, 3, 10, 7, 14, 0]);
+    /// assert_eq!(ssa.search("be".as_bytes()), (0, 2));
+    /// ```
+    pub fn word_starts(src: &'s [u8]) -> Self {
+        Self::with_predicate(src, |txt, i| {
+            txt[i].is_ascii_alphanumeric() &&
+                (i == 0 || !txt[i - 1].is_ascii_alphanumeric())
+        })
+    }
+
+    /// Number of suffixes
+    pub fn len(&self) -> usize {
+        self.sarray.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sarray.is_empty()
+    }
+
+    /// Search for the selected suffixes that have pat as a prefix. Returns
+    /// the (left, count) range of the sparse suffix array, as SA::search_sa.
+    pub fn search(&self, pat: &[u8]) -> (usize, usize) {
+        search_by(&self.sarray, pat, |p| self.txt.get(p).cloned())
+    }
+
+    /// LCP array of the sparse suffix array : lcp[0] = 0 and lcp[i] is the
+    /// length of the longest common prefix of the suffixes at ranks i-1 and
+    /// i. With the renamed blocks of new, the algorithm of Kasai et al. runs
+    /// over the renamed string, and only the first differing blocks are
+    /// compared, in O(n) time and O(k) words of memory. Otherwise the
+    /// suffixes are compared directly.
+    pub fn lcp(&self) -> Vec<T> {
+        let n = self.txt.len();
+        let k = self.len();
+        let sa: Vec<usize> = self.sarray.iter().map(|x| x.to_usize().unwrap()).collect();
+        let mut px: Vec<usize> = sa.clone();
+        px.sort_unstable();
+        let mut lcp: Vec<T> = vec![T::zero(); k];
+        match reduce(self.txt, &px) {
+            None => {
+                for r in 1..k {
+                    let l = common_prefix(&self.txt[sa[r - 1]..], &self.txt[sa[r]..]);
+                    lcp[r] = T::from_usize(l).unwrap();
+                }
+            }
+            Some((meta, _)) => {
+                // block index of the suffix at each rank, and its inverse
+                let idx: Vec<usize> = sa.iter().map(|p| px.binary_search(p).unwrap()).collect();
+                let mut rank: Vec<usize> = vec![0; k];
+                for (r, i) in idx.iter().enumerate() {
+                    rank[*i] = r;
+                }
+                // h blocks are shared with the suffix of the previous rank
+                let mut h: usize = 0;
+                for i in 0..k {
+                    let r = rank[i];
+                    if r == 0 {
+                        h = 0;
+                        continue;
+                    }
+                    let j = idx[r - 1];
+                    while i + h < k && j + h < k && meta[i + h] == meta[j + h] {
+                        h += 1;
+                    }
+                    let shared = if i + h < k { px[i + h] } else { n } - px[i];
+                    let rest = if i + h < k && j + h < k {
+                        common_prefix(block(self.txt, &px, i + h), block(self.txt, &px, j + h))
+                    } else {
+                        0
+                    };
+                    lcp[r] = T::from_usize(shared + rest).unwrap();
+                    h = h.saturating_sub(1);
+                }
+            }
+        }
+        lcp
+    }
+
+    /// Check that the suffixes are distinct positions of the text in strictly
+    /// increasing lexicographic order, by direct comparison.
+    pub fn check(&self) -> bool {
+        let n = self.txt.len();
+        self.sarray.iter().all(|x| x.to_usize().is_some_and(|p| p < n)) &&
+            self.sarray.windows(2).all(|w| {
+                let a = w[0].to_usize().unwrap();
+                let b = w[1].to_usize().unwrap();
+                self.txt[a..] < self.txt[b..]
+            })
+    }
+}
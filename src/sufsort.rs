@@ -115,6 +115,18 @@ impl<'s> SA<'s, i32> {
         }
     }
 
+    /// Search for each of pats, as search_sa, in input order. See
+    /// search_many for the batched search.
+    pub fn search_many(&self, pats: &[&[u8]]) -> Vec<(i32, i32)> {
+        self.search_many_parallel(pats, 1)
+    }
+
+    /// search_many with nthreads threads
+    pub fn search_many_parallel(&self, pats: &[&[u8]], nthreads: usize) -> Vec<(i32, i32)> {
+        search_many(self.txt, &self.sarray, pats, nthreads).into_iter()
+            .map(|(l, c)| (l as i32, c as i32)).collect()
+    }
+
     pub fn simple_search(&self, pc: char) -> (i32, i32){
         unsafe{
             let mut left:i32 = -1;
@@ -176,6 +188,18 @@ impl<'s> SA<'s, i64> {
         }
     }
 
+    /// Search for each of pats, as search_sa, in input order. See
+    /// search_many for the batched search.
+    pub fn search_many(&self, pats: &[&[u8]]) -> Vec<(i64, i64)> {
+        self.search_many_parallel(pats, 1)
+    }
+
+    /// search_many with nthreads threads
+    pub fn search_many_parallel(&self, pats: &[&[u8]], nthreads: usize) -> Vec<(i64, i64)> {
+        search_many(self.txt, &self.sarray, pats, nthreads).into_iter()
+            .map(|(l, c)| (l as i64, c as i64)).collect()
+    }
+
     pub fn simple_search(&self, pc: char) -> (i64, i64){
         unsafe{
             let mut left:i64 = -1;
@@ -191,7 +215,91 @@ impl<'s> SA<'s, i64> {
 }
 
 
-/// Construct the reverse lookup mapping corresponding to SA
+/// Read access to the entries of a suffix array, or of any array of text
+/// positions, whatever its storage : slices and vectors of integers, or the
+/// packed arrays of the packed module.
+pub trait SuffixArray {
+    /// Number of entries
+    fn len(&self) -> usize;
+
+    /// Entry i
+    fn at(&self, i: usize) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Write access to the entries of an array of text positions.
+pub trait SuffixArrayMut: SuffixArray {
+    /// Set the entry i to x
+    fn set(&mut self, i: usize, x: usize);
+}
+
+impl<T> SuffixArray for [T] where T: num::ToPrimitive {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn at(&self, i: usize) -> usize {
+        self[i].to_usize().unwrap()
+    }
+}
+
+impl<T> SuffixArray for Vec<T> where T: num::ToPrimitive {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn at(&self, i: usize) -> usize {
+        self[i].to_usize().unwrap()
+    }
+}
+
+impl<T> SuffixArrayMut for [T] where T: num::ToPrimitive + num::FromPrimitive {
+    fn set(&mut self, i: usize, x: usize) {
+        self[i] = T::from_usize(x).unwrap();
+    }
+}
+
+impl<T> SuffixArrayMut for Vec<T> where T: num::ToPrimitive + num::FromPrimitive {
+    fn set(&mut self, i: usize, x: usize) {
+        self[i] = T::from_usize(x).unwrap();
+    }
+}
+
+/// Iterator over the entries of a SuffixArray
+pub struct SAIter<'s, S: ?Sized> {
+    sa: &'s S,
+    i: usize,
+}
+
+impl<'s, S: SuffixArray + ?Sized> SAIter<'s, S> {
+    pub fn new(sa: &'s S) -> Self {
+        SAIter{sa, i: 0}
+    }
+}
+
+impl<'s, S: SuffixArray + ?Sized> Iterator for SAIter<'s, S> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        if self.i < self.sa.len() {
+            self.i += 1;
+            Some(self.sa.at(self.i - 1))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let r = self.sa.len() - self.i;
+        (r, Some(r))
+    }
+}
+
+/// Construct the reverse lookup mapping corresponding to SA. Panics if sa is
+/// not a permutation of 0..sa.len().
 ///
 /// #Example
 /// ```
@@ -199,29 +307,313 @@ impl<'s> SA<'s, i64> {
 /// let isa = sufsort_rs::sufsort::construct_isa(&sav);
 /// assert_eq!(isa,  &[4, 3, 10, 8, 2, 9, 7, 1, 6, 5, 0]);
 /// ```
-pub fn construct_isa<T>(sa: &Vec<T>) -> Vec<T>
-    where T: std::clone::Clone + std::marker::Copy + std::ops::Add +
-            num::ToPrimitive + num::One + num::Zero {
+pub fn construct_isa<T>(sa: &[T]) -> Vec<T>
+    where T: std::clone::Clone + std::marker::Copy +
+            num::ToPrimitive + num::FromPrimitive + num::Zero {
+    try_construct_isa(sa).expect("construct_isa: sa is not a permutation")
+}
+
+/// Construct the reverse lookup mapping corresponding to SA, or None if sa
+/// is not a permutation of 0..sa.len().
+///
+/// #Example
+/// ```
+/// let sav: Vec<i32> = vec![2, 0, -1];
+/// assert_eq!(sufsort_rs::sufsort::try_construct_isa(&sav), None);
+/// let sav: Vec<i32> = vec![2, 0, 1];
+/// assert_eq!(sufsort_rs::sufsort::try_construct_isa(&sav), Some(vec![1, 2, 0]));
+/// ```
+pub fn try_construct_isa<T>(sa: &[T]) -> Option<Vec<T>>
+    where T: std::clone::Clone + std::marker::Copy +
+            num::ToPrimitive + num::FromPrimitive + num::Zero {
+    // negative entries are out of range
+    if sa.iter().any(|x| x.to_usize().is_none()) {
+        return None;
+    }
     let mut isa: Vec<T> = vec![T::zero(); sa.len()];
-    let mut i: T = T::zero();
-
-    for t in sa {
-        match (*t).to_usize() {
-            Some(x) => {
-                // isa[(*t).to_usize().unwrap()] = i;
-                isa[x] = i.clone();
-                i = i + T::one();
-            },
-            None => ()
+    if try_construct_isa_into(sa, &mut isa) {
+        Some(isa)
+    } else {
+        None
+    }
+}
+
+/// Write the inverse of the suffix array sa to isa, of the same length, for
+/// any storage of both. Panics if sa is not a permutation of 0..sa.len().
+///
+/// #Example
+/// ```
+/// use sufsort_rs::sufsort::{construct_isa_into, SuffixArray};
+/// let sa40 = sufsort_rs::packed::SA40::new("MISSISSIPPI".as_bytes());
+/// let mut isa = sufsort_rs::packed::PackedSA::zeroed(sa40.len());
+/// construct_isa_into(&sa40, &mut isa);
+/// assert_eq!(isa.iter().collect::<Vec<usize>>(), &[4, 3, 10, 8, 2, 9, 7, 1, 6, 5, 0]);
+/// ```
+pub fn construct_isa_into<S, D>(sa: &S, isa: &mut D)
+    where S: SuffixArray + ?Sized, D: SuffixArrayMut + ?Sized {
+    assert!(try_construct_isa_into(sa, isa), "construct_isa: sa is not a permutation");
+}
+
+/// Write the inverse of the suffix array sa to isa, and return whether sa
+/// is a permutation of 0..sa.len(). Otherwise, the contents of isa are
+/// unspecified.
+pub fn try_construct_isa_into<S, D>(sa: &S, isa: &mut D) -> bool
+    where S: SuffixArray + ?Sized, D: SuffixArrayMut + ?Sized {
+    let n = sa.len();
+    assert!(isa.len() == n);
+    for i in 0..n {
+        let x = sa.at(i);
+        if x >= n {
+            return false;
         }
+        isa.set(x, i);
     }
+    // with n entries in range, some slot is left unset iff another one is
+    // set twice
+    check_inverse(sa, isa)
+}
+
+// whether sa[isa[j]] == j for the positions j of isa
+fn check_inverse<S, D>(sa: &S, isa: &D) -> bool
+    where S: SuffixArray + ?Sized, D: SuffixArray + ?Sized {
+    (0..isa.len()).all(|j| {
+        let k = isa.at(j);
+        k < sa.len() && sa.at(k) == j
+    })
+}
+
+/// Invert the permutation sa in place, turning a suffix array into its
+/// inverse or vice versa, without a second buffer. The cycles of the
+/// permutation are followed one by one, and the entries that are done are
+/// marked by complementing them, which puts them out of 0..sa.len() for
+/// signed and unsigned T alike. An unsigned T must thus hold 2 sa.len()
+/// values. Panics if sa is not a permutation of 0..sa.len(), in which case
+/// its contents are unspecified.
+///
+/// #Example
+/// ```
+/// let mut sav: Vec<u32> = vec![10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2];
+/// sufsort_rs::sufsort::invert_in_place(&mut sav);
+/// assert_eq!(sav,  &[4, 3, 10, 8, 2, 9, 7, 1, 6, 5, 0]);
+/// ```
+pub fn invert_in_place<T>(sa: &mut [T])
+    where T: std::marker::Copy + std::ops::Not<Output=T> +
+             num::ToPrimitive + num::FromPrimitive {
+    let n = sa.len();
+    if n == 0 {
+        return;
+    }
+    let in_range = |x: T| x.to_usize().is_some_and(|p| p < n);
+    assert!(!in_range(!T::from_usize(n - 1).unwrap()),
+            "invert_in_place: the entry type is too small to mark {} entries", n);
+    assert!(sa.iter().all(|x| in_range(*x)), "invert_in_place: sa is not a permutation");
+    for start in 0..n {
+        if !in_range(sa[start]) {
+            continue;
+        }
+        // the cycle start -> sa[start] -> ..., setting isa[sa[j]] = j
+        let mut prev = start;
+        let mut cur = sa[start].to_usize().unwrap();
+        while cur != start {
+            let next = sa[cur];
+            if !in_range(next) {
+                panic!("invert_in_place: sa is not a permutation");
+            }
+            sa[cur] = !T::from_usize(prev).unwrap();
+            prev = cur;
+            cur = next.to_usize().unwrap();
+        }
+        sa[start] = !T::from_usize(prev).unwrap();
+    }
+    for x in sa.iter_mut() {
+        *x = !*x;
+    }
+}
+
+// pointer to the inverse filled by construct_isa_parallel, whose threads
+// write disjoint entries
+struct SharedMut<T>(*mut T);
+
+unsafe impl<T: Send> Send for SharedMut<T> {}
+unsafe impl<T: Send> Sync for SharedMut<T> {}
+
+/// Construct the reverse lookup mapping corresponding to SA with nthreads
+/// threads. Each thread scans a contiguous range of sa and writes isa[sa[i]]
+/// = i, so that each entry of sa is read once. An atomic bitmap of the
+/// positions already written keeps the writes disjoint and detects the
+/// repeated positions. Panics if sa is not a permutation of 0..sa.len().
+///
+/// #Example
+/// ```
+/// let sav: Vec<i64> = vec![10, 7, 4, 1, 0, 9, 8, 6, 3, 5, 2];
+/// let isa = sufsort_rs::sufsort::construct_isa_parallel(&sav, 4);
+/// assert_eq!(isa,  &[4, 3, 10, 8, 2, 9, 7, 1, 6, 5, 0]);
+/// ```
+pub fn construct_isa_parallel<T>(sa: &[T], nthreads: usize) -> Vec<T>
+    where T: std::marker::Copy + std::marker::Send + std::marker::Sync +
+             num::ToPrimitive + num::FromPrimitive + num::Zero {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    let n = sa.len();
+    let mut isa: Vec<T> = vec![T::zero(); n];
+    if n == 0 {
+        return isa;
+    }
+    let seen: Vec<AtomicU64> = (0..n.div_ceil(64)).map(|_| AtomicU64::new(0)).collect();
+    let ptr = SharedMut(isa.as_mut_ptr());
+    let chunk = n.div_ceil(std::cmp::max(nthreads, 1));
+    let valid = std::thread::scope(|scope| {
+        let handles: Vec<_> = sa.chunks(chunk).enumerate().map(|(t, part)| {
+            let (seen, ptr) = (&seen, &ptr);
+            scope.spawn(move || {
+                for (k, x) in part.iter().enumerate() {
+                    let p = match x.to_usize() {
+                        Some(p) if p < n => p,
+                        _ => return false,
+                    };
+                    let bit = 1u64 << (p % 64);
+                    if seen[p / 64].fetch_or(bit, Ordering::Relaxed) & bit != 0 {
+                        return false;
+                    }
+                    // p < n, and only the thread that set the bit of p
+                    // writes isa[p]
+                    unsafe {
+                        *ptr.0.add(p) = T::from_usize(t * chunk + k).unwrap();
+                    }
+                }
+                true
+            })
+        }).collect();
+        handles.into_iter().all(|h| h.join().unwrap())
+    });
+    assert!(valid, "construct_isa_parallel: sa is not a permutation");
     isa
 }
 
+
+// range [l, r) of the suffixes in [l, r) whose character at depth d is c,
+// char_at(p) being the character at the text position p, None past the end
+fn refine<S, F>(sa: &S, l: usize, r: usize, d: usize, c: u8, char_at: &F) -> (usize, usize)
+    where S: SuffixArray + ?Sized, F: Fn(usize) -> Option<u8> {
+    let (mut lo, mut hi) = (l, r);
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if char_at(sa.at(mid) + d) < Some(c) { lo = mid + 1; } else { hi = mid; }
+    }
+    let left = lo;
+    hi = r;
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if char_at(sa.at(mid) + d) <= Some(c) { lo = mid + 1; } else { hi = mid; }
+    }
+    (left, lo)
+}
+
+/// Search for pat with the suffix array sa of a text given by char_at, the
+/// character at each text position or None past the end. Returns the (left,
+/// count) range of sa, as SA::search_sa; left is the insertion point of a
+/// pattern with no occurrences. The range is narrowed one character of pat
+/// at a time, in O(|pat| log n) time.
+pub fn search_by<S, F>(sa: &S, pat: &[u8], char_at: F) -> (usize, usize)
+    where S: SuffixArray + ?Sized, F: Fn(usize) -> Option<u8> {
+    let (mut l, mut r) = (0, sa.len());
+    for (d, c) in pat.iter().enumerate() {
+        if l == r {
+            break;
+        }
+        let (nl, nr) = refine(sa, l, r, d, *c, &char_at);
+        l = nl;
+        r = nr;
+    }
+    (l, r - l)
+}
+
+/// Search for pat in txt with its suffix array sa, in any storage. Returns
+/// the (left, count) range of sa, as search_by.
+///
+/// #Example
+/// ```
+/// let txt = "MISSISSIPPI".to_string();
+/// let psa = sufsort_rs::packed::PackedSA::new(txt.as_bytes());
+/// assert_eq!(sufsort_rs::sufsort::search(txt.as_bytes(), &psa, "ISS".as_bytes()), (2, 2));
+/// ```
+pub fn search<S>(txt: &[u8], sa: &S, pat: &[u8]) -> (usize, usize)
+    where S: SuffixArray + ?Sized {
+    assert!(txt.len() == sa.len());
+    search_by(sa, pat, |p| txt.get(p).cloned())
+}
+
+// search the patterns of order, sorted, keeping the interval of each prefix
+// of the previous pattern so that only the characters after the LCP with it
+// are searched
+fn search_sorted<S>(txt: &[u8], sa: &S, pats: &[&[u8]], order: &[usize])
+        -> Vec<(usize, usize)>
+    where S: SuffixArray + ?Sized {
+    let char_at = |p: usize| txt.get(p).cloned();
+    // intervals[d] : interval of the prefix of length d of the previous pattern
+    let mut intervals: Vec<(usize, usize)> = vec![(0, sa.len())];
+    let mut prev: &[u8] = &[];
+    order.iter().map(|&k| {
+        let pat = pats[k];
+        let h = prev.iter().zip(pat.iter()).take_while(|(a, b)| a == b).count();
+        intervals.truncate(std::cmp::min(h + 1, intervals.len()));
+        while intervals.len() <= pat.len() {
+            let d = intervals.len() - 1;
+            let (l, r) = intervals[d];
+            if l == r {
+                break;
+            }
+            intervals.push(refine(sa, l, r, d, pat[d], &char_at));
+        }
+        prev = pat;
+        let (l, r) = *intervals.last().unwrap();
+        (l, r - l)
+    }).collect()
+}
+
+/// Search for the patterns pats in txt with its suffix array sa, with
+/// nthreads threads. Returns the (left, count) range of sa for each
+/// pattern, in input order; left is the insertion point of a pattern with
+/// no occurrences. The patterns are sorted, so that each one starts from
+/// the interval of its longest common prefix with the previous one, and
+/// the sorted patterns are split into nthreads contiguous batches.
+///
+/// #Example
+/// ```
+/// let txt = "MISSISSIPPI".to_string();
+/// let say = sufsort_rs::sufsort::SA::<i64>::new(txt.as_bytes());
+/// let pats: Vec<&[u8]> = vec!["SSI".as_bytes(), "I".as_bytes(), "SI".as_bytes()];
+/// let rx = sufsort_rs::sufsort::search_many(txt.as_bytes(), &say.sarray, &pats, 2);
+/// assert_eq!(rx, &[(9, 2), (0, 4), (7, 2)]);
+/// ```
+pub fn search_many<S>(txt: &[u8], sa: &S, pats: &[&[u8]], nthreads: usize)
+        -> Vec<(usize, usize)>
+    where S: SuffixArray + std::marker::Sync + ?Sized {
+    assert!(txt.len() == sa.len());
+    let mut order: Vec<usize> = (0..pats.len()).collect();
+    order.sort_unstable_by_key(|k| pats[*k]);
+    let mut ranges: Vec<(usize, usize)> = vec![(0, 0); pats.len()];
+    if pats.is_empty() {
+        return ranges;
+    }
+    let chunk = pats.len().div_ceil(std::cmp::max(nthreads, 1));
+    let results: Vec<Vec<(usize, usize)>> = std::thread::scope(|scope| {
+        let handles: Vec<_> = order.chunks(chunk).map(|part| {
+            scope.spawn(move || search_sorted(txt, sa, pats, part))
+        }).collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for (k, rx) in order.iter().zip(results.into_iter().flatten()) {
+        ranges[*k] = rx;
+    }
+    ranges
+}
+
 pub struct BWT<'s, T>{
     pub txt:&'s [u8],
     pub sarray: Vec<T>,
-    pub bwt: Vec<u8>
+    pub bwt: Vec<u8>,
+    /// The position i such that SA[i] is 0, needed to invert the BWT.
+    pub idx: usize
 }
 
 impl<'s> BWT<'s, i32> {
@@ -245,7 +637,7 @@ impl<'s> BWT<'s, i32> {
             sax.set_len(src.len() + 1);
             dst.set_len(src.len());
             if rv > 0 && dst.len() >= (rv as usize) {
-                for i in 0..(rv as usize) {
+                for i in 0..(rv as usize - 1) {
                     dst[i] = dst[i + 1];
                 }
                 match src.last() {
@@ -253,7 +645,8 @@ impl<'s> BWT<'s, i32> {
                     None => ()
                 }
             }
-            BWT::<'s, i32>{txt: src, sarray: sax, bwt: dst}
+            let idx = if rv > 0 { (rv - 1) as usize } else { 0 };
+            BWT::<'s, i32>{txt: src, sarray: sax, bwt: dst, idx}
         }
     }
 }
@@ -282,7 +675,7 @@ impl<'s> BWT<'s, i64> {
             tmp.set_len(src.len() + 1);
             dst.set_len(src.len());
             if rv > 0 && dst.len() >= (rv as usize) {
-                for i in 0..(rv as usize) {
+                for i in 0..(rv as usize - 1) {
                     dst[i] = dst[i + 1];
                 }
                 match src.last() {
@@ -290,7 +683,8 @@ impl<'s> BWT<'s, i64> {
                     None => ()
                 }
             }
-            BWT::<'s, i64>{txt: src, sarray: tmp, bwt: dst}
+            let idx = if rv > 0 { (rv - 1) as usize } else { 0 };
+            BWT::<'s, i64>{txt: src, sarray: tmp, bwt: dst, idx}
         }
     }
 }
@@ -318,7 +712,7 @@ pub fn construct_bwt_sa(src: &[u8], sa: &mut Vec<i32>) -> Vec<u8>{
                                 src.len() as i32, pidx);
         dst.set_len(src.len());
         if rv == 0 && idx > 0 {
-            for i in 0..(idx as usize){
+            for i in 0..(idx as usize - 1){
                 dst[i] = dst[i + 1];
             }
             dst[(idx - 1) as usize] = src[src.len() - 1];
@@ -328,6 +722,49 @@ pub fn construct_bwt_sa(src: &[u8], sa: &mut Vec<i32>) -> Vec<u8>{
 }
 
 
-pub fn inverse_bwt(){
-    // TODO:: Need further design
+/// Invert the BWT constructed by BWT::new, given its idx. The BWT is
+/// completed to that of src followed by a sentinel, and src is spelled
+/// backwards from the sentinel row by LF-mapping.
+///
+/// #Example
+///
+/// ```
+/// let src = "MISSISSIPPI".to_string();
+/// let btx = sufsort_rs::sufsort::BWT::<i32>::new(src.as_bytes());
+/// let txt = sufsort_rs::sufsort::inverse_bwt(&btx.bwt, btx.idx);
+/// assert_eq!(txt, src.as_bytes());
+/// ```
+pub fn inverse_bwt(bwt: &[u8], idx: usize) -> Vec<u8> {
+    let n = bwt.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    assert!(idx < n);
+    // row 0 is the sentinel suffix, preceded by the last char, and row i+1
+    // is bwt[i], except for the row idx+1 that is preceded by the sentinel.
+    let mut counts: [usize; 257] = [0; 257];
+    for c in bwt {
+        counts[*c as usize + 1] += 1;
+    }
+    for c in 0..256 {
+        counts[c + 1] += counts[c];
+    }
+    let mut lf: Vec<usize> = vec![0; n + 1];
+    let mut last: Vec<u8> = vec![0; n + 1];
+    for row in 0..(n + 1) {
+        if row == idx + 1 {
+            continue;
+        }
+        let c = if row == 0 { bwt[idx] } else { bwt[row - 1] };
+        last[row] = c;
+        lf[row] = 1 + counts[c as usize];
+        counts[c as usize] += 1;
+    }
+    let mut txt: Vec<u8> = vec![0; n];
+    let mut row: usize = 0;
+    for k in (0..n).rev() {
+        txt[k] = last[row];
+        row = lf[row];
+    }
+    txt
 }
@@ -0,0 +1,250 @@
+// add the other case of the letters of set
+fn fold(set: &mut [bool; 256]) {
+    for c in b'a'..=b'z' {
+        let u = c.to_ascii_uppercase();
+        let either = set[c as usize] || set[u as usize];
+        set[c as usize] = either;
+        set[u as usize] = either;
+    }
+}
+
+/// Error of WildcardPattern::parse, with the position in the pattern of the
+/// offending `[` or `\`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WildcardError {
+    /// `[` with no matching `]`
+    UnterminatedClass(usize),
+    /// class matching no byte, such as `[]` or `[z-a]`
+    EmptyClass(usize),
+    /// `\` at the end of the pattern
+    TrailingEscape(usize),
+}
+
+impl std::fmt::Display for WildcardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            WildcardError::UnterminatedClass(i) => write!(f, "unterminated class at {}", i),
+            WildcardError::EmptyClass(i) => write!(f, "empty class at {}", i),
+            WildcardError::TrailingEscape(i) => write!(f, "trailing '\\' at {}", i),
+        }
+    }
+}
+
+impl std::error::Error for WildcardError {}
+
+/// Pattern with single-character wildcards and character classes. Each
+/// position matches a set of bytes; None stands for any byte.
+///
+/// The syntax is : `?` matches any byte, `[...]` any of the listed bytes
+/// or ranges such as `a-z`, `[^...]` any byte not listed, and `\` escapes
+/// the next byte, in or out of a class, e.g., `\]`. With case folding, letters match both cases, and a
+/// negated class excludes both cases of the letters it lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WildcardPattern {
+    pub symbols: Vec<Option<Vec<u8>>>,
+}
+
+impl WildcardPattern {
+    /// Parse pat. Fails on an unterminated or empty class, or a trailing
+    /// `\`.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::wildcard::{WildcardPattern, WildcardError};
+    /// let wp = WildcardPattern::parse("a?[b-d^]".as_bytes(), false).unwrap();
+    /// assert_eq!(wp.symbols, vec![Some(vec![b'a']), None,
+    ///                            Some(vec![b'^', b'b', b'c', b'd'])]);
+    /// assert_eq!(WildcardPattern::parse("a[bc".as_bytes(), false),
+    ///            Err(WildcardError::UnterminatedClass(1)));
+    /// ```
+    pub fn parse(pat: &[u8], fold_case: bool) -> Result<Self, WildcardError> {
+        let mut symbols: Vec<Option<Vec<u8>>> = Vec::new();
+        let mut i = 0;
+        while i < pat.len() {
+            let mut set: [bool; 256] = [false; 256];
+            match pat[i] {
+                b'?' => {
+                    symbols.push(None);
+                    i += 1;
+                    continue;
+                }
+                b'\\' => {
+                    if i + 1 == pat.len() {
+                        return Err(WildcardError::TrailingEscape(i));
+                    }
+                    set[pat[i + 1] as usize] = true;
+                    i += 2;
+                }
+                b'[' => {
+                    let negate = pat.get(i + 1) == Some(&b'^');
+                    let mut j = if negate { i + 2 } else { i + 1 };
+                    loop {
+                        if j == pat.len() {
+                            return Err(WildcardError::UnterminatedClass(i));
+                        }
+                        let mut c = pat[j];
+                        if c == b']' {
+                            break;
+                        }
+                        if c == b'\\' {
+                            if j + 1 == pat.len() {
+                                return Err(WildcardError::TrailingEscape(j));
+                            }
+                            j += 1;
+                            c = pat[j];
+                        }
+                        if j + 2 < pat.len() && pat[j + 1] == b'-' && pat[j + 2] != b']' {
+                            for x in c..=pat[j + 2] {
+                                set[x as usize] = true;
+                            }
+                            j += 3;
+                        } else {
+                            set[c as usize] = true;
+                            j += 1;
+                        }
+                    }
+                    if negate {
+                        if fold_case {
+                            fold(&mut set);
+                        }
+                        for x in set.iter_mut() {
+                            *x = !*x;
+                        }
+                    }
+                    if set.iter().all(|x| !*x) {
+                        return Err(WildcardError::EmptyClass(i));
+                    }
+                    i = j + 1;
+                }
+                c => {
+                    set[c as usize] = true;
+                    i += 1;
+                }
+            }
+            if fold_case {
+                fold(&mut set);
+            }
+            symbols.push(Some((0..256).filter(|c| set[*c]).map(|c| c as u8).collect()));
+        }
+        Ok(WildcardPattern{symbols})
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Occurrences of the pattern in txt, given its suffix array sa, as one
+    /// (expansion, left, count) for each concrete string matched by the
+    /// pattern that occurs in txt. The ranges of sa are disjoint and sorted.
+    /// The SA intervals are refined one position at a time, branching on
+    /// the characters allowed at that position.
+    ///
+    /// #Example
+    ///
+    /// ```
+    /// use sufsort_rs::wildcard::WildcardPattern;
+    /// let txt = "MISSISSIPPI".to_string();
+    /// let say = sufsort_rs::sufsort::SA::<i64>::new(txt.as_bytes());
+    /// let wp = WildcardPattern::parse("s[ip]?".as_bytes(), true).unwrap();
+    /// let hits = wp.search(txt.as_bytes(), &say.sarray);
+    /// assert_eq!(hits, vec![("SIP".as_bytes().to_vec(), 7, 1),
+    ///                       ("SIS".as_bytes().to_vec(), 8, 1)]);
+    /// assert_eq!(wp.locate(txt.as_bytes(), &say.sarray), &[3, 6]);
+    /// ```
+    pub fn search<T>(&self, txt: &[u8], sa: &[T]) -> Vec<(Vec<u8>, usize, usize)>
+        where T: num::ToPrimitive {
+        assert!(txt.len() == sa.len());
+        let mut hits: Vec<(Vec<u8>, usize, usize)> = Vec::new();
+        let mut prefix: Vec<u8> = Vec::with_capacity(self.len());
+        self.branch(txt, sa, 0, sa.len(), &mut prefix, &mut hits);
+        hits
+    }
+
+    // character at depth d of the suffix at sa[i], None past the end
+    fn char_at<T: num::ToPrimitive>(txt: &[u8], sa: &[T], i: usize, d: usize) -> Option<u8> {
+        txt.get(sa[i].to_usize().unwrap() + d).cloned()
+    }
+
+    // first index of [l, r) whose character at depth d is at least c
+    fn lower_bound<T: num::ToPrimitive>(txt: &[u8], sa: &[T], mut l: usize, mut r: usize,
+                                        d: usize, c: u8) -> usize {
+        while l < r {
+            let mid = l + (r - l) / 2;
+            if WildcardPattern::char_at(txt, sa, mid, d) < Some(c) { l = mid + 1; } else { r = mid; }
+        }
+        l
+    }
+
+    // the suffixes [l, r) of sa all start with prefix
+    fn branch<T>(&self, txt: &[u8], sa: &[T], l: usize, r: usize,
+                 prefix: &mut Vec<u8>, hits: &mut Vec<(Vec<u8>, usize, usize)>)
+        where T: num::ToPrimitive {
+        let d = prefix.len();
+        if d == self.len() {
+            hits.push((prefix.clone(), l, r - l));
+            return;
+        }
+        let mut children: Vec<(u8, usize, usize)> = Vec::new();
+        match self.symbols[d] {
+            None => {
+                // distinct characters of the interval, skipping the suffix
+                // of length d if any
+                let mut i = l;
+                while i < r {
+                    match WildcardPattern::char_at(txt, sa, i, d) {
+                        None => i += 1,
+                        Some(c) => {
+                            let j = if c == 255 { r } else {
+                                WildcardPattern::lower_bound(txt, sa, i, r, d, c + 1)
+                            };
+                            children.push((c, i, j));
+                            i = j;
+                        }
+                    }
+                }
+            }
+            Some(ref set) => {
+                let mut i = l;
+                for c in set {
+                    i = WildcardPattern::lower_bound(txt, sa, i, r, d, *c);
+                    if i == r {
+                        break;
+                    }
+                    let j = if *c == 255 { r } else {
+                        WildcardPattern::lower_bound(txt, sa, i, r, d, *c + 1)
+                    };
+                    if i < j {
+                        children.push((*c, i, j));
+                    }
+                    i = j;
+                }
+            }
+        }
+        for (c, i, j) in children {
+            prefix.push(c);
+            self.branch(txt, sa, i, j, prefix, hits);
+            prefix.pop();
+        }
+    }
+
+    /// Number of occurrences of the pattern in txt
+    pub fn count<T>(&self, txt: &[u8], sa: &[T]) -> usize
+        where T: num::ToPrimitive {
+        self.search(txt, sa).iter().map(|h| h.2).sum()
+    }
+
+    /// Sorted positions of the occurrences of the pattern in txt
+    pub fn locate<T>(&self, txt: &[u8], sa: &[T]) -> Vec<usize>
+        where T: num::ToPrimitive {
+        let mut occ: Vec<usize> = self.search(txt, sa).iter()
+            .flat_map(|h| sa[h.1..h.1 + h.2].iter().map(|x| x.to_usize().unwrap()))
+            .collect();
+        occ.sort_unstable();
+        occ
+    }
+}
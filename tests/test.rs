@@ -1,11 +1,31 @@
 #[cfg(test)]
 mod tests {
     extern crate sufsort_rs as ss;
+    extern crate flate2;
+    extern crate regex_automata;
     use self::ss::sufsort::SA;
     use self::ss::sufsort::BWT;
     use self::ss::sufsort::construct_bwt_sa;
     use self::ss::rmq::RMQ;
     use self::ss::rmq::find_min_element;
+    use self::ss::nsv::{construct_psv, construct_nsv};
+    use self::ss::lcp::lcp_intervals;
+    use self::ss::esa::EnhancedSA;
+    use self::ss::repeats;
+    use self::ss::fmindex::FMIndex;
+    use self::ss::matching::{MatchingIndex, longest_common_substring};
+    use self::ss::matching::{mems, mums, reverse_complement};
+    use self::ss::lz77::{lz77_factorize, Phrase};
+    use self::ss::lce::LCE;
+    use self::ss::runs;
+    use self::ss::lyndon::{lyndon_factorization, lyndon_array};
+    use self::ss::ebwt::{EBWT, bijective_bwt, inverse_bijective_bwt};
+    use self::ss::sais::construct_sa_int;
+    use self::ss::msbwt::{MultiFMIndex, TerminatorOrder, TERMINATOR};
+    use self::ss::compress;
+    use self::ss::external::{ExternalSuffixSorter, load_sa, load_i64};
+    use self::ss::external::construct_lcp_semi_external;
+    use self::ss::sparse::SparseSA;
 
     extern crate rand;
     use self::rand::Rng;
@@ -99,6 +119,18 @@ mod tests {
         assert_eq!(ilcp, mx_match);
     }
 
+    #[test]
+    fn test_sufsort_lcp_phi_random(){
+        let mut rng = rand::thread_rng();
+        for n in &[1, 2, 30, 200] {
+            let txt: Vec<u8> = (0..*n).map(|_| b'a' + rng.gen::<u8>() % 3).collect();
+            let say = SA::<i32>::new(&txt);
+            let isay = ss::sufsort::construct_isa(&say.sarray);
+            let ilcp = ss::lcp::construct_lcp_phi(&txt, &say.sarray);
+            assert_eq!(ilcp, ss::lcp::construct_lcp_kasai(&txt, &say.sarray, &isay));
+        }
+    }
+
     #[test]
     fn test_sufsort_lcp_kasai(){
         let txt = ("MISSISSIPPI").to_string();
@@ -450,4 +482,1231 @@ mod tests {
         // //std::cout << min_it - vec.cbegin() << std::endl;
     }
 
+
+    #[test]
+    fn test_psv_nsv(){
+        let mut rng = rand::thread_rng();
+        for n in &[0, 1, 2, 17, 100] {
+            let numbers: Vec<u32> = (0..*n).map(|_| rng.gen::<u32>() % 5).collect();
+            let psv = construct_psv(&numbers);
+            let nsv = construct_nsv(&numbers);
+            for i in 0..numbers.len() {
+                let bpsv = (0..i).rev().find(|&j| numbers[j] < numbers[i]);
+                let bnsv = (i+1..numbers.len()).find(|&j| numbers[j] < numbers[i]);
+                assert_eq!(psv[i], bpsv.unwrap_or(numbers.len()));
+                assert_eq!(nsv[i], bnsv.unwrap_or(numbers.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_lcp_intervals(){
+        let mut rng = rand::thread_rng();
+        for n in &[1, 2, 8, 50, 200] {
+            for sigma in &[1u8, 2, 4] {
+                let txt: Vec<u8> = (0..*n).map(|_| b'a' + rng.gen::<u8>() % sigma).collect();
+                let say = SA::<i32>::new(&txt);
+                let lcp = ss::lcp::construct_lcp_phi(&txt, &say.sarray);
+                let ivx: Vec<(usize, usize, usize)> = lcp_intervals(&lcp).collect();
+
+                // all the lcp-intervals by brute force
+                let mut bivx: Vec<(usize, usize, usize)> = Vec::new();
+                for i in 0..txt.len() {
+                    for j in i+1..txt.len() {
+                        let lv = (i+1..j+1).map(|k| lcp[k]).min().unwrap();
+                        if lcp[i] < lv && (j + 1 == txt.len() || lcp[j+1] < lv) {
+                            bivx.push((lv as usize, i, j));
+                        }
+                    }
+                }
+                // the root, if it is not already an lcp-interval
+                if txt.len() > 1 && (1..txt.len()).any(|k| lcp[k] == 0) {
+                    bivx.push((0, 0, txt.len() - 1));
+                }
+                let mut sivx = ivx.clone();
+                sivx.sort();
+                bivx.sort();
+                assert_eq!(sivx, bivx);
+
+                // bottom-up: nested intervals are reported first
+                for (x, &(_, l1, r1)) in ivx.iter().enumerate() {
+                    for &(_, l2, r2) in ivx[..x].iter() {
+                        assert!(!(l2 <= l1 && r1 <= r2));
+                    }
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_esa_navigation(){
+        let mut rng = rand::thread_rng();
+        for n in &[1, 2, 9, 60, 250] {
+            for sigma in &[1u8, 2, 4] {
+                let txt: Vec<u8> = (0..*n).map(|_| b'a' + rng.gen::<u8>() % sigma).collect();
+                let esa = EnhancedSA::<i32>::new(&txt);
+                let say = SA::<i32>::new(&txt);
+                assert_eq!(esa.sarray, say.sarray);
+                let links = ss::esa::SuffixLinks::new(&esa);
+
+                // children are split at the l-indices of each lcp-interval
+                for (lv, l, r) in lcp_intervals(&esa.lcp) {
+                    assert_eq!(esa.string_depth(l, r), lv);
+                    let mut cvx: Vec<(usize, usize)> = Vec::new();
+                    let mut cl = l;
+                    for k in l+1..r+1 {
+                        if esa.lcp[k] as usize == lv {
+                            cvx.push((cl, k - 1));
+                            cl = k;
+                        }
+                    }
+                    cvx.push((cl, r));
+                    assert_eq!(esa.children(l, r), cvx);
+                    for c in b'a'..b'a' + sigma + 1 {
+                        let cx = cvx.iter().cloned().find(|&(cl, _)| {
+                            txt.get(esa.sarray[cl] as usize + lv) == Some(&c)
+                        });
+                        assert_eq!(esa.child(l, r, c), cx);
+                    }
+
+                    // edge labels extend the parent label to the child label
+                    for &(cl, cr) in cvx.iter() {
+                        let sx = esa.sarray[cl] as usize;
+                        let mut lbx = txt[sx..sx + lv].to_vec();
+                        lbx.extend_from_slice(esa.edge_label((l, r), (cl, cr)));
+                        assert_eq!(&lbx[..], &txt[sx..sx + esa.string_depth(cl, cr)]);
+                    }
+
+                    // suffix links
+                    if lv > 0 {
+                        let sx = esa.sarray[l] as usize;
+                        let (left, count) = say.search_sa(&txt[sx+1..sx+lv]);
+                        assert_eq!(esa.suffix_link(l, r),
+                                   Some((left as usize, (left + count - 1) as usize)));
+                        assert_eq!(links.suffix_link(l, r), esa.suffix_link(l, r));
+                    }
+                }
+
+                // top-down pattern matching
+                for _ in 0..20 {
+                    let m = 1 + rng.gen::<usize>() % 6;
+                    let pat: Vec<u8> = (0..m).map(|_| b'a' + rng.gen::<u8>() % (sigma + 1)).collect();
+                    let (left, count) = say.search_sa(&pat);
+                    let rst = if count > 0 {
+                        Some((left as usize, (left + count - 1) as usize))
+                    } else {
+                        None
+                    };
+                    assert_eq!(esa.find(&pat), rst);
+                }
+            }
+        }
+    }
+
+
+    // occurrences of all the distinct repeated substrings of txt
+    fn brute_repeats(txt: &[u8]) -> Vec<(Vec<u8>, Vec<usize>)> {
+        let mut rpx: std::collections::BTreeMap<Vec<u8>, Vec<usize>> =
+            std::collections::BTreeMap::new();
+        for i in 0..txt.len() {
+            for j in i+1..txt.len()+1 {
+                rpx.entry(txt[i..j].to_vec()).or_default().push(i);
+            }
+        }
+        rpx.into_iter().filter(|x| x.1.len() > 1).collect()
+    }
+
+    #[test]
+    fn test_repeats(){
+        let mut rng = rand::thread_rng();
+        for n in &[1, 2, 10, 40, 90] {
+            for sigma in &[1u8, 2, 3] {
+                let txt: Vec<u8> = (0..*n).map(|_| b'a' + rng.gen::<u8>() % sigma).collect();
+                let say = SA::<i32>::new(&txt);
+                let lcp = ss::lcp::construct_lcp_phi(&txt, &say.sarray);
+                let brx = brute_repeats(&txt);
+
+                let lrs = repeats::longest_repeated_substring(&say.sarray, &lcp);
+                match brx.iter().map(|x| x.0.len()).max() {
+                    Some(mlen) => {
+                        let lrs = lrs.unwrap();
+                        assert_eq!(lrs.length, mlen);
+                        let w = &txt[lrs.positions[0]..lrs.positions[0] + mlen];
+                        assert_eq!(brx.iter().find(|x| &x.0[..] == w).unwrap().1,
+                                   lrs.positions);
+                    }
+                    None => assert!(lrs.is_none()),
+                }
+
+                // maximal : no common left or right extension
+                let bmax: Vec<(Vec<u8>, Vec<usize>)> = brx.iter().filter(|x| {
+                    let m = x.0.len();
+                    let lext = x.1.iter().map(|&p| if p > 0 { Some(txt[p-1]) } else { None });
+                    let rext = x.1.iter().map(|&p| txt.get(p + m).cloned());
+                    let lvx: Vec<Option<u8>> = lext.collect();
+                    let rvx: Vec<Option<u8>> = rext.collect();
+                    (lvx.contains(&None) || lvx.iter().any(|&c| c != lvx[0])) &&
+                    (rvx.contains(&None) || rvx.iter().any(|&c| c != rvx[0]))
+                }).cloned().collect();
+                for min_len in &[1, 3] {
+                    let mut rpx: Vec<(Vec<u8>, Vec<usize>)> =
+                        repeats::maximal_repeats(&txt, &say.sarray, &lcp, *min_len)
+                        .into_iter()
+                        .map(|x| (txt[x.positions[0]..x.positions[0] + x.length].to_vec(),
+                                  x.positions))
+                        .collect();
+                    rpx.sort();
+                    let bmx: Vec<(Vec<u8>, Vec<usize>)> = bmax.iter()
+                        .filter(|x| x.0.len() >= *min_len).cloned().collect();
+                    assert_eq!(rpx, bmx);
+                }
+
+                // supermaximal : not a substring of any other maximal repeat
+                let bsup: Vec<(Vec<u8>, Vec<usize>)> = bmax.iter().filter(|x| {
+                    !bmax.iter().any(|y| y.0.len() > x.0.len() &&
+                                     y.0.windows(x.0.len()).any(|w| w == &x.0[..]))
+                }).cloned().collect();
+                let mut rpx: Vec<(Vec<u8>, Vec<usize>)> =
+                    repeats::supermaximal_repeats(&txt, &say.sarray, &lcp, 1)
+                    .into_iter()
+                    .map(|x| (txt[x.positions[0]..x.positions[0] + x.length].to_vec(),
+                              x.positions))
+                    .collect();
+                rpx.sort();
+                assert_eq!(rpx, bsup);
+            }
+        }
+    }
+
+
+    fn random_text(n: usize, sigma: u8) -> Vec<u8> {
+        let mut rng = rand::thread_rng();
+        (0..n).map(|_| b'a' + rng.gen::<u8>() % sigma).collect()
+    }
+
+    // length of the longest prefix of pat occuring in txt
+    fn brute_match(txt: &[u8], pat: &[u8]) -> usize {
+        (0..pat.len()+1).rev().find(|&l| {
+            l == 0 || txt.windows(l).any(|w| w == &pat[..l])
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_fm_backward_search(){
+        for n in &[0, 1, 5, 63, 64, 65, 300] {
+            let txt = random_text(*n, 3);
+            let say = SA::<i32>::new(&txt);
+            let fmx = FMIndex::new(&txt, &say.sarray);
+            for m in 1..6 {
+                for _ in 0..10 {
+                    let pat = random_text(m, 4);
+                    let (left, count) = say.search_sa(&pat);
+                    let (fleft, fcount) = fmx.backward_search(&pat);
+                    assert_eq!(fcount, count as usize);
+                    if count > 0 {
+                        assert_eq!(fleft, left as usize);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_matching_statistics(){
+        for n in &[0, 1, 7, 100, 400] {
+            for sigma in &[1u8, 2, 4] {
+                let txt = random_text(*n, *sigma);
+                let say = SA::<i64>::new(&txt);
+                let mx = MatchingIndex::new(&txt, &say.sarray);
+                let query = random_text(60, sigma + 1);
+                let ms = mx.matching_statistics(&query);
+                for i in 0..query.len() {
+                    assert_eq!(ms[i], brute_match(&txt, &query[i..]));
+                }
+
+                let lcs = longest_common_substring(&txt, &query);
+                let blen = ms.iter().cloned().max().unwrap_or(0);
+                match lcs {
+                    Some((pa, pb, len)) => {
+                        assert_eq!(len, blen);
+                        assert_eq!(&txt[pa..pa + len], &query[pb..pb + len]);
+                    }
+                    None => assert_eq!(blen, 0),
+                }
+            }
+        }
+    }
+
+
+    // maximal exact matches (ref_pos, query_pos, length), and whether the
+    // match occurs exactly once in both strings
+    fn brute_mems(rx: &[u8], qx: &[u8]) -> Vec<(usize, usize, usize, bool)> {
+        let count = |s: &[u8], w: &[u8]| s.windows(w.len()).filter(|x| *x == w).count();
+        let mut mx = Vec::new();
+        for i in 0..qx.len() {
+            for p in 0..rx.len() {
+                if i > 0 && p > 0 && rx[p-1] == qx[i-1] {
+                    continue;
+                }
+                let mut l = 0;
+                while i + l < qx.len() && p + l < rx.len() && qx[i+l] == rx[p+l] {
+                    l += 1;
+                }
+                if l > 0 {
+                    let w = &qx[i..i+l];
+                    mx.push((p, i, l, count(rx, w) == 1 && count(qx, w) == 1));
+                }
+            }
+        }
+        mx.sort_by_key(|x| (x.1, x.0));
+        mx
+    }
+
+    #[test]
+    fn test_mems_mums(){
+        for n in &[1, 10, 50, 120] {
+            for sigma in &[2u8, 4] {
+                let rx = random_text(*n, *sigma);
+                let qx = random_text(*n / 2 + 3, *sigma);
+                for min_len in &[1, 3] {
+                    let bmx: Vec<(usize, usize, usize, bool)> = brute_mems(&rx, &qx)
+                        .into_iter().filter(|x| x.2 >= *min_len).collect();
+                    let mx = mems(&rx, &qx, *min_len, false);
+                    let bm: Vec<(usize, usize, usize)> =
+                        bmx.iter().map(|x| (x.0, x.1, x.2)).collect();
+                    assert_eq!(mx.forward, bm);
+                    assert!(mx.reverse.is_empty());
+                    let ux = mums(&rx, &qx, *min_len, false);
+                    let bu: Vec<(usize, usize, usize)> =
+                        bmx.iter().filter(|x| x.3).map(|x| (x.0, x.1, x.2)).collect();
+                    assert_eq!(ux.forward, bu);
+                }
+            }
+        }
+
+        let rx = "AACGTTTGCAGGATCCA".as_bytes();
+        let qx = "TGGATCCTGCAAACG".as_bytes();
+        let mx = mems(rx, qx, 4, true);
+        assert_eq!(mx.reverse, &[(2, 0, 15)]);
+        // reverse hits are on the forward query
+        let qx = "GGGTTGCAAAAAA".as_bytes();
+        let mx = mems(rx, qx, 4, true);
+        assert_eq!(mx.reverse, &[(4, 4, 6)]); // TTTGCA, reverse complement of TGCAAA
+        for (r, q, len) in mx.reverse.iter().chain(mums(rx, qx, 4, true).reverse.iter()) {
+            assert_eq!(reverse_complement(&qx[*q..*q + *len]), &rx[*r..*r + *len]);
+        }
+        assert_eq!(reverse_complement("ACGTNacgt".as_bytes()), "acgtNACGT".as_bytes());
+    }
+
+
+    // greedy LZ77 phrase lengths, with or without overlapping sources
+    fn brute_lz77(txt: &[u8], overlap: bool) -> Vec<usize> {
+        let mut lens = Vec::new();
+        let mut i = 0;
+        while i < txt.len() {
+            let mut best = 0;
+            for j in 0..i {
+                let mut l = 0;
+                while i + l < txt.len() && txt[j+l] == txt[i+l] && (overlap || j + l < i) {
+                    l += 1;
+                }
+                best = std::cmp::max(best, l);
+            }
+            lens.push(best);
+            i += std::cmp::max(best, 1);
+        }
+        lens
+    }
+
+    #[test]
+    fn test_lz77(){
+        for n in &[0, 1, 2, 20, 100, 400] {
+            for sigma in &[1u8, 2, 4] {
+                let txt = random_text(*n, *sigma);
+                let lz = lz77_factorize(&txt);
+                for (phrases, overlap) in [(&lz.overlapping, true),
+                                           (&lz.non_overlapping, false)].iter() {
+                    let mut i = 0;
+                    let mut lens = Vec::new();
+                    for p in phrases.iter() {
+                        match *p {
+                            Phrase::Literal(c) => {
+                                assert_eq!(c, txt[i]);
+                                lens.push(0);
+                            },
+                            Phrase::Copy{source, length} => {
+                                assert!(source < i && (*overlap || source + length <= i));
+                                assert_eq!(txt[source..source+length], txt[i..i+length]);
+                                // the non-overlapping source is the leftmost one
+                                assert!(*overlap || !(0..source).any(|j| {
+                                    txt[j..j+length] == txt[i..i+length]
+                                }));
+                                lens.push(length);
+                            },
+                        }
+                        i += p.length();
+                    }
+                    assert_eq!(lens, brute_lz77(&txt, *overlap));
+                }
+            }
+        }
+    }
+
+
+    // runs as (start, length, period), from the maximal stretches of each
+    // period with no smaller period
+    fn brute_runs(txt: &[u8]) -> Vec<(usize, usize, usize)> {
+        let n = txt.len();
+        let has_period = |s: usize, e: usize, q: usize| (s..e-q).all(|k| txt[k] == txt[k+q]);
+        let mut rx = Vec::new();
+        for p in 1..n/2+1 {
+            let mut s = 0;
+            while s + p < n {
+                if txt[s] != txt[s+p] {
+                    s += 1;
+                    continue;
+                }
+                let mut e = s + p;
+                while e < n && txt[e] == txt[e-p] {
+                    e += 1;
+                }
+                if e - s >= 2 * p && !(1..p).any(|q| has_period(s, e, q)) {
+                    rx.push((s, e - s, p));
+                }
+                s = e - p + 1;
+            }
+        }
+        rx.sort_by_key(|x| (x.0, x.2));
+        rx
+    }
+
+    #[test]
+    fn test_runs(){
+        for n in &[1, 2, 10, 60, 200] {
+            for sigma in &[1u8, 2, 3] {
+                let txt = random_text(*n, *sigma);
+                let say = SA::<i64>::new(&txt);
+                let lcp = ss::lcp::construct_lcp_phi(&txt, &say.sarray);
+                let lce = LCE::new(&say.sarray, &lcp);
+                for _ in 0..20 {
+                    let i = rand::thread_rng().gen::<usize>() % n;
+                    let j = rand::thread_rng().gen::<usize>() % n;
+                    let mut l = 0;
+                    while i + l < *n && j + l < *n && txt[i+l] == txt[j+l] {
+                        l += 1;
+                    }
+                    assert_eq!(lce.lce(i, j), l);
+                }
+                let rx: Vec<(usize, usize, usize)> = runs::runs(&txt).iter()
+                    .map(|x| (x.start, x.length, x.period)).collect();
+                assert_eq!(rx, brute_runs(&txt));
+            }
+        }
+    }
+
+
+    fn is_lyndon(w: &[u8]) -> bool {
+        !w.is_empty() && (1..w.len()).all(|k| w < &w[k..] && w[..k] < w[k..])
+    }
+
+    #[test]
+    fn test_lyndon(){
+        for n in &[0, 1, 2, 10, 50, 150] {
+            for sigma in &[1u8, 2, 4] {
+                let txt = random_text(*n, *sigma);
+                let fx = lyndon_factorization(&txt);
+                let mut i = 0;
+                for (k, &(s, l)) in fx.iter().enumerate() {
+                    assert_eq!(s, i);
+                    assert!(is_lyndon(&txt[s..s+l]));
+                    if k > 0 {
+                        let (ps, pl) = fx[k-1];
+                        assert!(txt[ps..ps+pl] >= txt[s..s+l]);
+                    }
+                    i += l;
+                }
+                assert_eq!(i, *n);
+
+                let say = SA::<i32>::new(&txt);
+                let isa = ss::sufsort::construct_isa(&say.sarray);
+                let lyn = lyndon_array(&isa);
+                for i in 0..*n {
+                    let l = (1..n-i+1).rev().find(|&l| is_lyndon(&txt[i..i+l])).unwrap();
+                    assert_eq!(lyn[i], l);
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_ebwt(){
+        let mut rng = rand::thread_rng();
+        for k in &[1, 2, 5, 20] {
+            for sigma in &[1u8, 2, 4] {
+                let strings: Vec<Vec<u8>> = (0..*k)
+                    .map(|_| random_text(1 + rng.gen::<usize>() % 8, *sigma)).collect();
+                let refs: Vec<&[u8]> = strings.iter().map(|x| &x[..]).collect();
+                let ebx = EBWT::new(&refs);
+                assert_eq!(ebx.inverse(), strings);
+
+                // sort the rotations by a long enough prefix of their powers
+                let mlen = strings.iter().map(|x| x.len()).max().unwrap();
+                let mut rotx: Vec<(Vec<u8>, u8)> = Vec::new();
+                for x in strings.iter() {
+                    for j in 0..x.len() {
+                        let w: Vec<u8> = (0..2*mlen).map(|t| x[(j + t) % x.len()]).collect();
+                        rotx.push((w, x[(j + x.len() - 1) % x.len()]));
+                    }
+                }
+                rotx.sort();
+                let bwt: Vec<u8> = rotx.iter().map(|x| x.1).collect();
+                assert_eq!(ebx.bwt, bwt);
+            }
+        }
+        for n in &[0, 1, 2, 10, 100, 300] {
+            for sigma in &[1u8, 2, 4] {
+                let txt = random_text(*n, *sigma);
+                let bbx = bijective_bwt(&txt);
+                assert_eq!(inverse_bijective_bwt(&bbx), txt);
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_sais(){
+        let mut rng = rand::thread_rng();
+        for n in &[0, 1, 2, 3, 10, 100, 1000] {
+            for upper in &[0usize, 1, 3, 300] {
+                let txt: Vec<usize> = (0..*n).map(|_| rng.gen::<usize>() % (upper + 1)).collect();
+                let mut sa: Vec<usize> = (0..*n).collect();
+                sa.sort_by_key(|&i| &txt[i..]);
+                assert_eq!(construct_sa_int(&txt, *upper), sa);
+            }
+        }
+        let txt = random_text(500, 4);
+        let say = SA::<i32>::new(&txt);
+        let itxt: Vec<usize> = txt.iter().map(|c| *c as usize).collect();
+        let sa: Vec<i32> = construct_sa_int(&itxt, 255).iter().map(|x| *x as i32).collect();
+        assert_eq!(sa, say.sarray);
+    }
+
+    #[test]
+    fn test_multi_bwt(){
+        let mut rng = rand::thread_rng();
+        for k in &[1, 3, 30] {
+            for order in &[TerminatorOrder::Input, TerminatorOrder::Lexicographic] {
+                let strings: Vec<Vec<u8>> = (0..*k)
+                    .map(|_| random_text(rng.gen::<usize>() % 12, 3)).collect();
+                let refs: Vec<&[u8]> = strings.iter().map(|x| &x[..]).collect();
+                let fmx = MultiFMIndex::<u32>::new(&refs, *order);
+                let mbx = &fmx.mbwt;
+                assert_eq!(mbx.len(), strings.iter().map(|x| x.len() + 1).sum::<usize>());
+                let wide = ss::msbwt::MultiBWT::<usize>::new(&refs, *order);
+                assert_eq!(wide.sarray, mbx.sarray.iter().map(|x| *x as usize).collect::<Vec<usize>>());
+                assert_eq!(wide.bwt, mbx.bwt);
+
+                // terminator ranks, and the terminator rows of the bwt
+                let mut ids: Vec<usize> = (0..*k).collect();
+                if *order == TerminatorOrder::Lexicographic {
+                    ids.sort_by_key(|&i| &strings[i]);
+                }
+                for (r, i) in ids.iter().enumerate() {
+                    assert_eq!(mbx.term_ranks[*i], r);
+                }
+                let tx: Vec<usize> = (0..mbx.len()).filter(|&r| mbx.bwt[r] == TERMINATOR)
+                    .map(|r| mbx.string_at(mbx.sarray[r] as usize).0).collect();
+                assert_eq!(tx, mbx.term_ids);
+                let mut sorted = tx.clone();
+                sorted.sort();
+                assert_eq!(sorted, (0..*k).collect::<Vec<usize>>());
+
+                for (i, s) in strings.iter().enumerate() {
+                    assert_eq!(&fmx.read(i), s);
+                }
+                for m in 1..4 {
+                    let pat = random_text(m, 3);
+                    let mut px = Vec::new();
+                    for (i, s) in strings.iter().enumerate() {
+                        for j in 0..(s.len() + 1).saturating_sub(m) {
+                            if s[j..j+m] == pat[..] {
+                                px.push((i, j));
+                            }
+                        }
+                    }
+                    assert_eq!(fmx.locate(&pat), px);
+                    let mut ix: Vec<usize> = px.iter().map(|x| x.0).collect();
+                    ix.dedup();
+                    assert_eq!(fmx.reads_containing(&pat), ix);
+                }
+            }
+        }
+        // the concatenation must fit the suffix array type
+        let long = vec![b'A'; 300];
+        assert!(std::panic::catch_unwind(|| {
+            ss::msbwt::MultiBWT::<u8>::new(&[&long[..]], TerminatorOrder::Input)
+        }).is_err());
+    }
+
+
+    #[test]
+    fn test_inverse_bwt(){
+        for n in &[0, 1, 2, 10, 1000] {
+            for sigma in &[1u8, 2, 20] {
+                let txt = random_text(*n, *sigma);
+                let btx = ss::sufsort::BWT::<i32>::new(&txt);
+                assert_eq!(ss::sufsort::inverse_bwt(&btx.bwt, btx.idx), txt);
+                let btx = ss::sufsort::BWT::<i64>::new(&txt);
+                assert_eq!(ss::sufsort::inverse_bwt(&btx.bwt, btx.idx), txt);
+            }
+        }
+    }
+
+    #[test]
+    fn test_compress(){
+        let mut rng = rand::thread_rng();
+        let mut inputs: Vec<Vec<u8>> = vec![Vec::new(), vec![7], vec![0; 5000]];
+        inputs.push((0..20000).map(|_| rng.gen::<u8>()).collect());
+        inputs.push(random_text(20000, 4));
+        inputs.push("the quick brown fox jumps over the lazy dog. ".repeat(300).into_bytes());
+        for txt in inputs.iter() {
+            for block_size in &[1, 100, 4096, compress::DEFAULT_BLOCK_SIZE] {
+                if *block_size == 1 && txt.len() > 5000 {
+                    continue;
+                }
+                let mut packed: Vec<u8> = Vec::new();
+                compress::compress_blocks(&txt[..], &mut packed, *block_size).unwrap();
+                let mut unpacked: Vec<u8> = Vec::new();
+                compress::decompress(&packed[..], &mut unpacked).unwrap();
+                assert_eq!(&unpacked, txt);
+            }
+        }
+        let mut packed: Vec<u8> = Vec::new();
+        compress::compress(&inputs[5][..], &mut packed).unwrap();
+        assert!(packed.len() < inputs[5].len() / 10);
+        let mut unpacked: Vec<u8> = Vec::new();
+        assert!(compress::decompress("not compressed".as_bytes(), &mut unpacked).is_err());
+        // a huge payload length in a short stream : magic, version, block
+        // size, then the block length, idx and 258 code lengths
+        let mut packed: Vec<u8> = Vec::new();
+        compress::compress(&inputs[5][..], &mut packed).unwrap();
+        packed[275..279].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(compress::decompress(&packed[..], &mut unpacked).is_err());
+    }
+
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("sufsort_rs_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_external_sa(){
+        let dir = test_dir("external");
+        for n in &[0, 1, 2, 100, 5000] {
+            for sigma in &[1u8, 2, 4] {
+                let txt = random_text(*n, *sigma);
+                std::fs::write(dir.join("txt"), &txt).unwrap();
+                // small runs, to merge several of them
+                let sorter = ExternalSuffixSorter::new(&dir, 24 * 1024);
+                assert_eq!(sorter.construct(dir.join("txt"), dir.join("sa")).unwrap(), *n);
+                let say = load_sa(&txt, dir.join("sa")).unwrap();
+                assert_eq!(say.sarray, SA::<i64>::new(&txt).sarray);
+                if *n > 0 {
+                    assert!(say.check_sa(false));
+                }
+            }
+        }
+        // 20 runs of 1024 records merged 2 at a time, over several levels
+        let sorter = ExternalSuffixSorter::new(&dir, 0);
+        assert_eq!(sorter.fan_in(), 2);
+        let txt = random_text(20000, 2);
+        std::fs::write(dir.join("txt"), &txt).unwrap();
+        sorter.construct(dir.join("txt"), dir.join("sa")).unwrap();
+        assert_eq!(load_sa(&txt, dir.join("sa")).unwrap().sarray, SA::<i64>::new(&txt).sarray);
+        assert!(load_sa("ab".as_bytes(), dir.join("sa")).is_err());
+        // only the output files are left
+        let mut files: Vec<String> = std::fs::read_dir(&dir).unwrap()
+            .map(|x| x.unwrap().file_name().into_string().unwrap()).collect();
+        files.sort();
+        assert_eq!(files, &["sa", "txt"]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+
+    #[test]
+    fn test_semi_external_lcp(){
+        let dir = test_dir("lcp");
+        for n in &[0, 1, 2, 100, 3000] {
+            for sigma in &[1u8, 2, 4] {
+                let txt = random_text(*n, *sigma);
+                std::fs::write(dir.join("txt"), &txt).unwrap();
+                let sorter = ExternalSuffixSorter::new(&dir, 1 << 20);
+                sorter.construct(dir.join("txt"), dir.join("sa")).unwrap();
+                let say = SA::<i64>::new(&txt);
+                let lcp = ss::lcp::construct_lcp_phi(&txt, &say.sarray);
+                for q in &[1, 3, 64] {
+                    construct_lcp_semi_external(&txt, dir.join("sa"), dir.join("lcp"), *q).unwrap();
+                    assert_eq!(load_i64(dir.join("lcp")).unwrap(), lcp);
+                }
+            }
+        }
+        assert!(construct_lcp_semi_external("abc".as_bytes(), dir.join("sa"),
+                                            dir.join("lcp"), 4).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+
+    #[test]
+    fn test_sparse_sa(){
+        for n in &[0, 1, 10, 200, 1000] {
+            for sigma in &[1u8, 2, 4] {
+                let txt = random_text(*n, *sigma);
+                let say = SA::<i32>::new(&txt);
+                let lcp = ss::lcp::construct_lcp_phi(&txt, &say.sarray);
+                // every position : the full suffix array
+                let full = SparseSA::<i32>::every_kth(&txt, 1);
+                assert_eq!(full.sarray, say.sarray);
+                assert_eq!(full.lcp(), lcp);
+                for k in &[2, 5] {
+                    let ssa = SparseSA::<i64>::every_kth(&txt, *k);
+                    assert!(ssa.check());
+                    let sampled: Vec<i64> = say.sarray.iter()
+                        .filter(|x| (**x as usize).is_multiple_of(*k)).map(|x| *x as i64).collect();
+                    assert_eq!(ssa.sarray, sampled);
+                    let brute: Vec<i64> = (0..sampled.len()).map(|i| if i == 0 { 0 } else {
+                        let (x, y) = (&txt[sampled[i-1] as usize..], &txt[sampled[i] as usize..]);
+                        x.iter().zip(y.iter()).take_while(|(c, d)| c == d).count() as i64
+                    }).collect();
+                    assert_eq!(ssa.lcp(), brute);
+                    for m in 1..4 {
+                        let pat = random_text(m, *sigma);
+                        let (left, count) = ssa.search(&pat);
+                        let occ: Vec<i64> = sampled.iter().cloned()
+                            .filter(|p| txt[*p as usize..].starts_with(&pat)).collect();
+                        assert_eq!(count, occ.len());
+                        assert_eq!(&ssa.sarray[left..left+count], &occ[..]);
+                    }
+                }
+            }
+        }
+        // random positions, some of whose blocks are prefixes of others
+        let mut rng = rand::thread_rng();
+        for n in &[1, 50, 500] {
+            for sigma in &[1u8, 2, 4] {
+                let txt = random_text(*n, *sigma);
+                let px: Vec<usize> = (0..*n).filter(|_| rng.gen::<u8>() < 80).collect();
+                let ssa = SparseSA::<i64>::new(&txt, &px);
+                let mut sorted = px.clone();
+                sorted.sort_by(|a, b| txt[*a..].cmp(&txt[*b..]));
+                assert_eq!(ssa.sarray, sorted.iter().map(|x| *x as i64).collect::<Vec<i64>>());
+                let brute: Vec<i64> = (0..sorted.len()).map(|i| if i == 0 { 0 } else {
+                    txt[sorted[i-1]..].iter().zip(txt[sorted[i]..].iter())
+                        .take_while(|(c, d)| c == d).count() as i64
+                }).collect();
+                assert_eq!(ssa.lcp(), brute);
+            }
+        }
+        // a^n : comparison sorting would take quadratic time
+        let n = 200000;
+        let txt = vec![b'a'; n];
+        let ssa = SparseSA::<i64>::every_kth(&txt, 3);
+        let px: Vec<i64> = (0..n as i64).rev().filter(|p| p % 3 == 0).collect();
+        assert_eq!(ssa.sarray, px);
+        let lcp: Vec<i64> = (0..px.len()).map(|i| if i == 0 { 0 } else { n as i64 - px[i-1] }).collect();
+        assert_eq!(ssa.lcp(), lcp);
+        let txt = "a b a".as_bytes();
+        let mut ssa = SparseSA::<i32>::word_starts(txt);
+        assert_eq!(ssa.sarray, &[4, 0, 2]);
+        ssa.sarray.swap(0, 1);
+        assert!(!ssa.check());
+    }
+
+
+    #[test]
+    fn test_isa_variants(){
+        use self::ss::sufsort::{construct_isa, try_construct_isa, invert_in_place,
+                                construct_isa_parallel};
+        let mut rng = rand::thread_rng();
+        for n in &[0, 1, 2, 100, 5000] {
+            let txt = random_text(*n, 3);
+            let say = SA::<i64>::new(&txt);
+            let isa = construct_isa(&say.sarray);
+            for (i, x) in say.sarray.iter().enumerate() {
+                assert_eq!(isa[*x as usize], i as i64);
+            }
+            assert_eq!(try_construct_isa(&say.sarray), Some(isa.clone()));
+            for t in &[1, 3, 8] {
+                assert_eq!(construct_isa_parallel(&say.sarray, *t), isa);
+            }
+            let mut sax = say.sarray.clone();
+            invert_in_place(&mut sax);
+            assert_eq!(sax, isa);
+            invert_in_place(&mut sax);
+            assert_eq!(sax, say.sarray);
+            // unsigned entries
+            let mut sau: Vec<u32> = say.sarray.iter().map(|x| *x as u32).collect();
+            invert_in_place(&mut sau);
+            assert_eq!(sau, isa.iter().map(|x| *x as u32).collect::<Vec<u32>>());
+            let mut saz: Vec<usize> = say.sarray.iter().map(|x| *x as usize).collect();
+            invert_in_place(&mut saz);
+            assert_eq!(saz, isa.iter().map(|x| *x as usize).collect::<Vec<usize>>());
+            if *n > 1 {
+                let mut bad = say.sarray.iter().map(|x| *x as u32).collect::<Vec<u32>>();
+                bad[0] = bad[1];
+                assert!(std::panic::catch_unwind(move || invert_in_place(&mut bad)).is_err());
+            }
+
+            if *n > 1 {
+                // a duplicate, a negative and an out of range entry
+                let mut bad = say.sarray.clone();
+                let k = rng.gen::<usize>() % (*n - 1);
+                bad[k] = bad[k + 1];
+                assert_eq!(try_construct_isa(&bad), None);
+                bad[k] = -1;
+                assert_eq!(try_construct_isa(&bad), None);
+                bad[k] = *n as i64;
+                assert_eq!(try_construct_isa(&bad), None);
+                assert!(std::panic::catch_unwind(|| construct_isa_parallel(&bad, 2)).is_err());
+                assert!(std::panic::catch_unwind(move || invert_in_place(&mut bad)).is_err());
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_succinct_plcp(){
+        use self::ss::bits::BitVector;
+        use self::ss::lcp::{construct_plcp, SuccinctPLCP};
+        let mut rng = rand::thread_rng();
+        for len in &[0, 1, 63, 64, 65, 1000, 5000] {
+            for density in &[1, 2, 50] {
+                let bits: Vec<bool> = (0..*len).map(|_| rng.gen::<usize>() % density == 0).collect();
+                let ones: Vec<usize> = (0..*len).filter(|&i| bits[i]).collect();
+                let bv = BitVector::from_positions(*len, ones.clone());
+                assert_eq!(bv.count_ones(), ones.len());
+                for (k, p) in ones.iter().enumerate() {
+                    assert_eq!(bv.select1(k), *p);
+                }
+                for (i, b) in bits.iter().enumerate() {
+                    assert_eq!(bv.get(i), *b);
+                    assert_eq!(bv.rank1(i), ones.iter().filter(|&&p| p < i).count());
+                }
+                assert_eq!(bv.rank1(*len), ones.len());
+            }
+        }
+        for n in &[0, 1, 100, 3000] {
+            for sigma in &[1u8, 4] {
+                let txt = random_text(*n, *sigma);
+                let say = SA::<i32>::new(&txt);
+                let lcp = ss::lcp::construct_lcp_phi(&txt, &say.sarray);
+                let plcp = construct_plcp(&txt, &say.sarray);
+                let slcp = SuccinctPLCP::from_sa(&txt, &say.sarray);
+                assert_eq!(slcp.len(), *n);
+                for i in 0..*n {
+                    assert_eq!(plcp[say.sarray[i] as usize], lcp[i]);
+                    assert_eq!(slcp.plcp(i), plcp[i] as usize);
+                    assert_eq!(slcp.lcp(&say.sarray, i), lcp[i] as usize);
+                }
+                if *n >= 3000 {
+                    assert!(slcp.size_in_bytes() < *n);
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_compact_lcp(){
+        use self::ss::lcp::CompactLCP;
+        for n in &[0, 1, 100, 2000] {
+            for sigma in &[1u8, 2, 4] {
+                // long repeats, for values above 255
+                let mut txt = random_text(*n, *sigma);
+                let copy = txt.clone();
+                txt.extend(copy);
+                let say = SA::<i64>::new(&txt);
+                let lcp = ss::lcp::construct_lcp_phi(&txt, &say.sarray);
+                let clcp = CompactLCP::from_sa(&txt, &say.sarray);
+                assert_eq!(clcp.len(), txt.len());
+                assert_eq!(clcp.exceptions(), lcp.iter().filter(|x| **x >= 255).count());
+                let vx: Vec<i64> = clcp.iter().map(|x| x as i64).collect();
+                assert_eq!(vx, lcp);
+                for (i, x) in lcp.iter().enumerate() {
+                    assert_eq!(clcp.get(i), *x as usize);
+                }
+                if *n == 2000 {
+                    assert!(clcp.exceptions() > 0);
+                }
+            }
+        }
+        let txt = random_text(5000, 4);
+        let say = SA::<i64>::new(&txt);
+        let clcp = CompactLCP::from_sa(&txt, &say.sarray);
+        assert_eq!(clcp.size_in_bytes(), txt.len());
+    }
+
+
+    #[test]
+    fn test_packed_sa(){
+        use self::ss::packed::{PackedSA, SA40};
+        use self::ss::sufsort::{SuffixArray, search, construct_isa_into};
+        use self::ss::lcp::{CompactLCP, SuccinctPLCP, construct_plcp_into};
+        let dir = test_dir("packed");
+        for n in &[0, 1, 2, 100, 3000] {
+            for sigma in &[1u8, 4] {
+                let txt = random_text(*n, *sigma);
+                let say = SA::<i64>::new(&txt);
+                let psa = PackedSA::new(&txt);
+                let sa40 = SA40::new(&txt);
+                let sax: Vec<usize> = say.sarray.iter().map(|x| *x as usize).collect();
+                assert_eq!(psa.iter().collect::<Vec<usize>>(), sax);
+                assert_eq!(sa40.iter().collect::<Vec<usize>>(), sax);
+                assert_eq!(psa.len(), *n);
+                assert_eq!(sa40.size_in_bytes(), 5 * n);
+                assert!(psa.size_in_bytes() <= 8 * (n * psa.width as usize).div_ceil(64));
+
+                let isa = ss::sufsort::construct_isa(&say.sarray);
+                let mut pisa = PackedSA::zeroed(*n);
+                construct_isa_into(&sa40, &mut pisa);
+                assert_eq!(pisa.iter().map(|x| x as i64).collect::<Vec<i64>>(), isa);
+                let lcp = ss::lcp::construct_lcp_phi(&txt, &say.sarray);
+                let clcp = CompactLCP::from_sa(&txt, &psa);
+                assert_eq!(clcp.iter().map(|x| x as i64).collect::<Vec<i64>>(), lcp);
+                let mut plcp = PackedSA::zeroed(*n);
+                construct_plcp_into(&txt, &sa40, &mut plcp);
+                let rplcp = ss::lcp::construct_plcp(&txt, &say.sarray);
+                assert_eq!(plcp.iter().map(|x| x as i64).collect::<Vec<i64>>(), rplcp);
+                let slcp = SuccinctPLCP::from_sa(&txt, &sa40);
+                assert!((0..*n).all(|i| slcp.lcp(&psa, i) as i64 == lcp[i]));
+                for m in 1..4 {
+                    let pat = random_text(m, *sigma);
+                    let (left, count) = say.search_sa(&pat);
+                    let rx = if count > 0 { (left as usize, count as usize) } else { (0, 0) };
+                    let px = search(&txt, &psa, &pat);
+                    assert_eq!(if px.1 > 0 { px } else { (0, 0) }, rx);
+                    assert_eq!(search(&txt, &sa40, &pat), px);
+                    assert_eq!(search(&txt, &say.sarray, &pat), px);
+                }
+
+                let bytes: Vec<u8> = say.sarray.iter().flat_map(|x| x.to_le_bytes()).collect();
+                std::fs::write(dir.join("sa"), &bytes).unwrap();
+                let fsa = PackedSA::from_i64_file(dir.join("sa")).unwrap();
+                assert_eq!(fsa.iter().collect::<Vec<usize>>(), sax);
+                let fsa = SA40::from_i64_file(dir.join("sa")).unwrap();
+                assert_eq!(fsa.iter().collect::<Vec<usize>>(), sax);
+            }
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+
+    #[test]
+    fn test_dna_index(){
+        use self::ss::dna::{self, DnaIndex, PackedDNA, Strand};
+        let mut rng = rand::thread_rng();
+        let alphabet = b"ACGTacgtNNRY";
+        for n in &[0, 1, 10, 500, 2000] {
+            let seq: Vec<u8> = (0..*n).map(|_| alphabet[rng.gen_range(0, alphabet.len())]).collect();
+            let plain: Vec<u8> = seq.iter().map(|c| match c.to_ascii_uppercase() {
+                b'R' | b'Y' => b'N', x => x }).collect();
+            let packed = PackedDNA::new(&seq);
+            assert_eq!(packed.unpack(), plain);
+            assert_eq!((0..*n).map(|i| dna::decode(packed.get(i))).collect::<Vec<u8>>(), plain);
+            assert!(packed.size_in_bytes() <= n / 4 + 8 + 16 * packed.n_runs().len());
+            assert_eq!(dna::reverse_complement(&dna::reverse_complement(&seq)), seq);
+
+            let dix = DnaIndex::new(&seq);
+            let codes: Vec<u8> = plain.iter().map(|c| dna::encode(*c).unwrap()).collect();
+            let say = SA::<i64>::new(&codes);
+            if *n > 0 {
+                let btx = ss::sufsort::BWT::<i64>::new(&codes);
+                let bwt: Vec<u8> = btx.bwt.iter().map(|x| dna::decode(*x)).collect();
+                assert_eq!(dix.bwt(), (bwt, btx.idx));
+            }
+            for (i, p) in dix.sarray.iter().enumerate() {
+                assert_eq!(p as i64, say.sarray[i]);
+            }
+            for m in 1..5 {
+                let pat: Vec<u8> = (0..m).map(|_| b"ACGT"[rng.gen_range(0, 4)]).collect();
+                let rc = dna::reverse_complement(&pat);
+                let find = |p: &[u8]| -> Vec<usize> {
+                    (0..(n + 1).saturating_sub(m)).filter(|i| &plain[*i..*i + m] == p).collect()
+                };
+                assert_eq!(dix.locate(&pat), find(&pat));
+                let mut both: Vec<(usize, Strand)> = find(&pat).into_iter().map(|p| (p, Strand::Forward))
+                    .chain(find(&rc).into_iter().map(|p| (p, Strand::Reverse))).collect();
+                both.sort();
+                assert_eq!(dix.locate_both(&pat), both);
+                assert_eq!(dix.count(&pat.to_ascii_lowercase()), find(&pat).len());
+            }
+            assert_eq!(dix.count(b"N"), 0);
+        }
+        // gaps are stored as N, soft-masked regions as upper case
+        let packed = PackedDNA::new(b"AC-Gt*..acgT");
+        assert_eq!(packed.unpack(), b"ACNGTNNNACGT");
+        assert_eq!(packed.n_runs(), &[(2, 1), (5, 3)]);
+        assert_eq!(dna::encode(b'x'), None);
+    }
+
+
+    #[test]
+    fn test_seqio(){
+        use std::io::Write;
+        use self::ss::seqio::{self, Record, SeqText, FastqReader};
+        let dir = test_dir("seqio");
+        let mut rng = rand::thread_rng();
+        let records: Vec<Record> = (0..20).map(|i| {
+            let seq = random_text(rng.gen_range(0, 200), 4).iter().map(|c| b"ACGT"[(*c - b'a') as usize]).collect();
+            Record{name: format!("seq{}", i), seq, qual: None}
+        }).collect();
+        let mut fasta = String::new();
+        let mut fastq = String::new();
+        for rec in &records {
+            fasta.push_str(&format!(">{} description\r\n", rec.name));
+            for line in rec.seq.chunks(60) {
+                fasta.push_str(std::str::from_utf8(line).unwrap());
+                fasta.push('\n');
+            }
+            let seq = std::str::from_utf8(&rec.seq).unwrap();
+            fastq.push_str(&format!("@{}\n{}\n+\n{}\n", rec.name, seq, "I".repeat(seq.len())));
+        }
+        std::fs::write(dir.join("a.fa"), &fasta).unwrap();
+        let mut gz = flate2::write::GzEncoder::new(std::fs::File::create(dir.join("a.fq.gz")).unwrap(),
+                                                   flate2::Compression::default());
+        gz.write_all(fastq.as_bytes()).unwrap();
+        gz.finish().unwrap();
+
+        let fq: Vec<Record> = seqio::read_records(dir.join("a.fq.gz")).unwrap();
+        assert_eq!(fq.len(), records.len());
+        assert!(fq.iter().zip(records.iter()).all(|(a, b)| a.name == b.name && a.seq == b.seq &&
+                                                  a.qual.as_ref().map(|q| q.len()) == Some(b.seq.len())));
+        let mut fa = seqio::records(dir.join("a.fa")).unwrap();
+        assert_eq!(fa.next().unwrap().unwrap().name, records[0].name);
+        assert_eq!(fa.count(), records.len() - 1);
+        std::fs::write(dir.join("empty.fa"), "").unwrap();
+        assert!(seqio::records(dir.join("empty.fa")).unwrap().next().is_none());
+        let stx = SeqText::from_path(dir.join("a.fa")).unwrap();
+        let stq = SeqText::from_path(dir.join("a.fq.gz")).unwrap();
+        assert_eq!(stx.text, stq.text);
+        assert_eq!(stx.sequences(), records.iter().map(|r| &r.seq[..]).collect::<Vec<&[u8]>>());
+
+        let say = SA::<i64>::new(&stx.text);
+        for m in 1..5 {
+            let pat: Vec<u8> = (0..m).map(|_| b"ACGT"[rng.gen_range(0, 4)]).collect();
+            let (left, count) = say.search_sa(&pat);
+            let hits = stx.hits(&say.sarray, left as usize, count as usize);
+            let mut expected: Vec<(&str, usize)> = Vec::new();
+            for rec in &records {
+                for p in 0..(rec.seq.len() + 1).saturating_sub(m) {
+                    if rec.seq[p..p + m] == pat[..] {
+                        expected.push((&rec.name, p));
+                    }
+                }
+            }
+            assert_eq!(hits, expected);
+        }
+        assert!(FastqReader::new("@r\nACG\n+\nII\n".as_bytes()).next().unwrap().is_err());
+        let mut std = SeqText::default();
+        std.push("r1", b"ACG");
+        std.push("r2", b"TT");
+        assert_eq!(std.offsets, &[0, 4, 7]);
+        assert_eq!(std.sequence(1), b"TT");
+        assert_eq!(std.resolve(4), Some((1, 0)));
+        assert_eq!(std.resolve(3), None);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+
+    fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+        let mut row: Vec<usize> = (0..=b.len()).collect();
+        for (i, x) in a.iter().enumerate() {
+            let mut prev = row[0];
+            row[0] = i + 1;
+            for (j, y) in b.iter().enumerate() {
+                let cur = std::cmp::min(prev + usize::from(x != y), std::cmp::min(row[j], row[j + 1]) + 1);
+                prev = row[j + 1];
+                row[j + 1] = cur;
+            }
+        }
+        row[b.len()]
+    }
+
+    #[test]
+    fn test_approx_search(){
+        use self::ss::approx::{search_hamming, search_edit};
+        for n in &[1, 10, 300] {
+            for sigma in &[2u8, 4] {
+                let txt = random_text(*n, *sigma);
+                let say = SA::<i64>::new(&txt);
+                let fmx = FMIndex::new(&txt, &say.sarray);
+                for m in 1..6 {
+                    let pat = random_text(m, *sigma);
+                    for k in 0..3 {
+                        let expected: Vec<(usize, usize)> = (0..(n + 1).saturating_sub(m)).map(|p| {
+                            (p, txt[p..p + m].iter().zip(pat.iter()).filter(|(a, b)| a != b).count())
+                        }).filter(|h| h.1 <= k).collect();
+                        assert_eq!(search_hamming(&fmx, &pat, k), expected);
+
+                        let mut expected: Vec<(usize, usize, usize)> = Vec::new();
+                        for p in 0..*n {
+                            let best = (1..=std::cmp::min(m + k, n - p))
+                                .map(|l| (edit_distance(&pat, &txt[p..p + l]), l)).min().unwrap();
+                            if best.0 <= k {
+                                expected.push((p, best.1, best.0));
+                            }
+                        }
+                        assert_eq!(search_edit(&fmx, &pat, k), expected);
+                    }
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_bidirectional_fm(){
+        use self::ss::bifm::BiFMIndex;
+        use self::ss::approx::search_hamming;
+        let mut rng = rand::thread_rng();
+        for n in &[1, 10, 400] {
+            for sigma in &[2u8, 4] {
+                let txt = random_text(*n, *sigma);
+                let rtxt: Vec<u8> = txt.iter().rev().cloned().collect();
+                let say = SA::<i64>::new(&txt);
+                let rsay = SA::<i64>::new(&rtxt);
+                let bfm = BiFMIndex::new(&txt, &say.sarray, &rtxt, &rsay.sarray);
+                let fmx = FMIndex::new(&txt, &say.sarray);
+                let rfm = FMIndex::new(&rtxt, &rsay.sarray);
+                for _ in 0..20 {
+                    // grow a pattern by random extensions on both sides
+                    let mut pat: std::collections::VecDeque<u8> = std::collections::VecDeque::new();
+                    let mut iv = bfm.full();
+                    for _ in 0..6 {
+                        let c = b'a' + rng.gen::<u8>() % sigma;
+                        if rng.gen::<bool>() {
+                            pat.push_front(c);
+                            iv = bfm.extend_left(c, iv);
+                        } else {
+                            pat.push_back(c);
+                            iv = bfm.extend_right(c, iv);
+                        }
+                        let px: Vec<u8> = pat.iter().cloned().collect();
+                        let (left, count) = fmx.backward_search(&px);
+                        assert_eq!(iv.size, count);
+                        if count == 0 {
+                            break;
+                        }
+                        assert_eq!(iv.fwd, left + 1);
+                        let rpx: Vec<u8> = px.iter().rev().cloned().collect();
+                        assert_eq!(iv.rev, rfm.backward_search(&rpx).0 + 1);
+                        assert_eq!(bfm.locate(iv), bfm.locate(bfm.search(&px)));
+                    }
+                }
+                for m in 1..6 {
+                    let pat = random_text(m, *sigma);
+                    for k in 0..3 {
+                        assert_eq!(bfm.hamming(&pat, k), search_hamming(&fmx, &pat, k));
+                    }
+                }
+            }
+        }
+    }
+
+
+    #[test]
+    fn test_wildcard_search(){
+        use self::ss::wildcard::{WildcardPattern, WildcardError};
+        let mut rng = rand::thread_rng();
+        let pieces: Vec<&[u8]> = vec![b"a", b"b", b"C", b"?", b"[ab]", b"[^a]", b"[a-c]", b"\\?"];
+        for n in &[0usize, 1, 20, 500] {
+            let txt: Vec<u8> = (0..*n).map(|_| b"abcABC?"[rng.gen_range(0, 7)]).collect();
+            let say = SA::<i64>::new(&txt);
+            for _ in 0..50 {
+                let m = rng.gen_range(1, 5);
+                let pat: Vec<u8> = (0..m).flat_map(|_| pieces[rng.gen_range(0, pieces.len())].to_vec()).collect();
+                let fold = rng.gen::<bool>();
+                let wp = WildcardPattern::parse(&pat, fold).unwrap();
+                assert_eq!(wp.len(), m);
+                let matches = |p: usize| (0..m).all(|j| match wp.symbols[j] {
+                    None => true,
+                    Some(ref set) => set.contains(&txt[p + j]),
+                });
+                let expected: Vec<usize> = (0..(n + 1).saturating_sub(m)).filter(|p| matches(*p)).collect();
+                assert_eq!(wp.locate(&txt, &say.sarray), expected);
+                assert_eq!(wp.count(&txt, &say.sarray), expected.len());
+                let hits = wp.search(&txt, &say.sarray);
+                for (i, (exp, left, count)) in hits.iter().enumerate() {
+                    let (l, c) = say.search_sa(exp);
+                    assert_eq!((*left, *count), (l as usize, c as usize));
+                    assert!(i == 0 || hits[i - 1].1 + hits[i - 1].2 <= *left);
+                }
+            }
+        }
+        let wp = WildcardPattern::parse(b"[^a-z]", true).unwrap();
+        assert!(wp.symbols[0].as_ref().unwrap().iter().all(|c| !c.is_ascii_alphabetic()));
+        let wp = WildcardPattern::parse(b"[\\]\\\\]\\]", false).unwrap();
+        assert_eq!(wp.symbols, vec![Some(vec![b'\\', b']']), Some(vec![b']'])]);
+        // malformed patterns
+        assert_eq!(WildcardPattern::parse(b"ab[cd", false), Err(WildcardError::UnterminatedClass(2)));
+        assert_eq!(WildcardPattern::parse(b"[^", false), Err(WildcardError::UnterminatedClass(0)));
+        assert_eq!(WildcardPattern::parse(b"a[]", false), Err(WildcardError::EmptyClass(1)));
+        assert_eq!(WildcardPattern::parse(b"[z-a]", false), Err(WildcardError::EmptyClass(0)));
+        assert_eq!(WildcardPattern::parse(b"ab\\", false), Err(WildcardError::TrailingEscape(2)));
+        assert_eq!(WildcardPattern::parse(b"[a\\", false), Err(WildcardError::TrailingEscape(2)));
+    }
+
+
+    #[test]
+    fn test_regex_search(){
+        use self::ss::regex::regex_search;
+        use self::regex_automata::meta::Regex;
+        use self::regex_automata::{Anchored, Input};
+        let mut rng = rand::thread_rng();
+        let patterns = [r"ab", r"a+b", r"a.c", r"(ab|ca)+", r"(?-u:\b)ab", r"(?m:^)a[bc]*",
+                        r"b$", r"c?", r"[^a]a", r"(?-u:\wb\b)"];
+        for n in &[0usize, 1, 30, 600] {
+            let txt: Vec<u8> = (0..*n).map(|_| b"abc \n"[rng.gen_range(0, 5)]).collect();
+            let say = SA::<i64>::new(&txt);
+            for re in patterns.iter() {
+                let occ = regex_search(&txt, &say.sarray, re).unwrap();
+                let rx = Regex::new(re).unwrap();
+                let expected: Vec<usize> = (0..*n).filter(|p| {
+                    rx.is_match(Input::new(&txt).range(*p..).anchored(Anchored::Yes))
+                }).collect();
+                assert_eq!(occ, expected, "{}", re);
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_many(){
+        let mut rng = rand::thread_rng();
+        for n in &[0usize, 1, 50, 2000] {
+            for sigma in &[2u8, 4] {
+                let txt = random_text(*n, *sigma);
+                let say = SA::<i64>::new(&txt);
+                let sax = SA::<i32>::new(&txt);
+                let owned: Vec<Vec<u8>> = (0..300).map(|_| {
+                    let m = rng.gen_range(0, 8);
+                    random_text(m, sigma + 1)
+                }).collect();
+                let pats: Vec<&[u8]> = owned.iter().map(|p| &p[..]).collect();
+                let rx = say.search_many(&pats);
+                assert_eq!(say.search_many_parallel(&pats, 4), rx);
+                let rx32 = sax.search_many_parallel(&pats, 3);
+                for (k, pat) in pats.iter().enumerate() {
+                    let count = (0..*n).filter(|p| txt[*p..].starts_with(pat)).count();
+                    assert_eq!(rx[k].1 as usize, count);
+                    assert_eq!((rx32[k].0 as i64, rx32[k].1 as i64), rx[k]);
+                    if count > 0 {
+                        assert_eq!(rx[k], say.search_sa(pat));
+                    }
+                }
+            }
+        }
+    }
 }